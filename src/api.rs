@@ -2,5 +2,58 @@
 //!
 //! This module contains the functions for fetching the data from the API and deserializing it into the required data structures.
 
+use anyhow::Context;
+use reqwest::Url;
+use crate::env::Variable;
+
+pub mod cache;
+pub mod date;
 pub mod deserial;
-pub mod fetch;
\ No newline at end of file
+pub mod fetch;
+pub mod provider;
+pub mod target;
+
+pub use date::Date;
+pub use deserial::{Release, Download};
+pub use fetch::Fetch;
+pub use provider::{Provider, ReleaseProvider};
+pub use target::Target;
+
+/// Returns the base URL of the releases API.
+///
+/// This is configured with [`Variable::ApiUrl`], and always ends with a trailing slash so that it can be joined.
+///
+/// # Errors
+/// This function will return an error if the configured URL is not valid.
+pub fn base() -> anyhow::Result<Url> {
+    let base = Variable::ApiUrl.get::<String>();
+    let base = format!("{}/", base.trim_end_matches('/'));
+
+    Url::parse(&base)
+        .with_context(|| format!("Invalid API URL: {base}"))
+}
+
+/// Returns the URL of an endpoint of the releases API (e.g. `products/releases`).
+///
+/// # Errors
+/// This function will return an error if the configured URL is not valid.
+pub fn endpoint(path: &str) -> anyhow::Result<Url> {
+    base()?
+        .join(path.trim_start_matches('/'))
+        .with_context(|| format!("Invalid API endpoint: {path}"))
+}
+
+/// Resolves a link returned by the releases API.
+///
+/// Absolute links are returned as-is, while relative links are resolved against the API base URL.
+/// This allows a mirror or a local server to serve its own downloads and checksums.
+///
+/// # Errors
+/// This function will return an error if the link cannot be resolved.
+pub fn resolve(link: &str) -> anyhow::Result<String> {
+    let url = base()?
+        .join(link)
+        .with_context(|| format!("Invalid link: {link}"))?;
+
+    Ok(url.to_string())
+}
@@ -0,0 +1,104 @@
+//! Module for caching responses from `JetBrains`' API.
+//!
+//! Releases are cached on disk for each product code and release type, so that repeated queries do not hit the network.
+//! Entries are kept apart for each source URL (e.g. the API base URL or a release feed), so that switching to or from
+//! a mirror never serves the other's releases.
+//! Entries are stored in the cache directory and expire after the configured TTL, unless running offline.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+use crate::env::Variable;
+use crate::tool::release;
+
+/// Returns the cached releases of a product fetched from a source URL, if any.
+///
+/// A cached list of all releases also satisfies a request for the latest release only.
+/// Expired entries are ignored, unless running offline.
+#[must_use]
+pub fn get(source: &str, code: &str, types: &[release::Type], latest: bool) -> Option<serde_json::Value> {
+    let mut paths = vec![path(source, code, types, false)];
+    if latest {
+        paths.push(path(source, code, types, true));
+    }
+
+    let offline = Variable::Offline.get_bool();
+    let ttl = ttl();
+
+    paths.into_iter().find_map(|path| {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if !offline && age >= ttl {
+            crate::debug!("Cache entry {} is expired", path.display());
+            return None;
+        }
+
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(value) => {
+                crate::debug!("Using cached releases from {}", path.display());
+                Some(value)
+            }
+            Err(err) => {
+                crate::debug!("Ignoring invalid cache entry {}: {err}", path.display());
+                None
+            }
+        }
+    })
+}
+
+/// Stores the releases of a product fetched from a source URL in the cache.
+///
+/// Nothing is stored if the cache is disabled (a TTL of 0).
+///
+/// # Errors
+/// This function will return an error if the cache entry cannot be written.
+pub fn put(source: &str, code: &str, types: &[release::Type], latest: bool, value: &serde_json::Value) -> anyhow::Result<()> {
+    if ttl().is_zero() {
+        return Ok(());
+    }
+
+    let path = path(source, code, types, latest);
+    let directory = path.parent().unwrap();
+
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+    // Write to a temporary file first, so that concurrent readers never see a partial entry
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temp, value.to_string())
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, &path)
+        .with_context(|| format!("Failed to move {} to {}", temp.display(), path.display()))?;
+
+    crate::debug!("Cached releases to {}", path.display());
+
+    Ok(())
+}
+
+/// Returns the time during which cached releases are considered fresh.
+#[must_use]
+pub fn ttl() -> Duration {
+    Duration::from_secs(Variable::CacheTtl.get_u64())
+}
+
+/// Returns the path of the cache entry for a product, in the directory of its source URL.
+fn path(source: &str, code: &str, types: &[release::Type], latest: bool) -> PathBuf {
+    let types = types.iter()
+        .map(release::Type::as_str)
+        .collect::<Vec<_>>()
+        .join("+");
+
+    let source = format!("{:x}", Sha256::digest(source.trim_end_matches('/').as_bytes()));
+
+    Variable::CacheDirectory.get::<PathBuf>()
+        .join("releases")
+        .join(&source[..16])
+        .join(format!("{code}_{types}{}.json", if latest { "_latest" } else { "" }))
+}
@@ -0,0 +1,174 @@
+//! Date types and parsing
+//!
+//! This module contains a simple calendar date, used for release dates.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::SystemTime;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// A calendar date (e.g. 2024-04-04)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[readonly::make]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Creates a new date.
+    ///
+    /// # Errors
+    /// This function will return an error if the month is out of range, or if the day is not in the month.
+    pub fn new(year: u16, month: u8, day: u8) -> anyhow::Result<Self> {
+        if !(1..=12).contains(&month) {
+            anyhow::bail!("Invalid month: {month}");
+        }
+        if !(1..=days_in_month(year, month)).contains(&day) {
+            anyhow::bail!("Invalid day: {day} (not in {year:04}-{month:02})");
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    /// Parses a date leniently, as found in release feeds of any source.
+    ///
+    /// The date may be followed by a time (e.g. `2024-04-04T10:00:00Z`), and its parts may be separated by
+    /// dashes, slashes or dots (e.g. `2024/04/04`). This returns `None` if no valid date is found.
+    #[must_use]
+    pub fn parse_lenient(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let end = s.find(|c: char| !c.is_ascii_digit() && !matches!(c, '-' | '/' | '.'))
+            .unwrap_or(s.len());
+
+        let parts = s[..end].split(['-', '/', '.']).collect::<Vec<_>>();
+        let [year, month, day] = parts.as_slice() else {
+            return None;
+        };
+
+        Self::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?).ok()
+    }
+
+    /// Returns the current date, in UTC.
+    ///
+    /// # Panics
+    /// This function will panic if the system time is before the Unix epoch.
+    #[must_use]
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("System time is before the Unix epoch")
+            .as_secs();
+
+        Self::from_days(i64::try_from(seconds / 86400).unwrap_or(i64::MAX))
+    }
+
+    /// Returns the number of days since the Unix epoch.
+    #[must_use]
+    pub fn days(&self) -> i64 {
+        // See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Returns the date for a number of days since the Unix epoch.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn from_days(days: i64) -> Self {
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    /// Returns the number of days elapsed since this date.
+    ///
+    /// This is negative if the date is in the future.
+    #[must_use]
+    pub fn age(&self) -> i64 {
+        Self::today().days() - self.days()
+    }
+}
+
+/// Returns the number of days in a month of a year.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No year found"))?;
+        let month = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No month found"))?;
+        let day = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No day found"))?;
+
+        let year = year.parse::<u16>()
+            .with_context(|| format!("Failed to parse year: {year}"))?;
+        let month = month.parse::<u8>()
+            .with_context(|| format!("Failed to parse month: {month}"))?;
+        let day = day.parse::<u8>()
+            .with_context(|| format!("Failed to parse day: {day}"))?;
+
+        Self::new(year, month, day)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
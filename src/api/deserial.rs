@@ -1,86 +1,169 @@
 //! Module for deserializing responses from `JetBrains`' API.
 
 use std::collections::HashMap;
-use anyhow::bail;
-use serde::Deserialize;
-use crate::tool::{release, version::Version, build::Build};
+use serde::{Deserialize, Serialize};
+use crate::tool::{release, version::{Version, Major}, build::Build};
+use crate::archive::Format;
+use crate::util::Checksum;
+use super::{Date, Target};
 
 /// The deserialized release data from `JetBrains`' API.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Release {
     #[serde(rename = "type")]
     pub release: release::Type,
+    /// The release type as displayed by `JetBrains` (e.g. "Release Candidate"), if it differs from the type
+    #[serde(rename = "printableReleaseType")]
+    pub printable_release: Option<String>,
 
     pub version: Version,
+    /// The major version, if given by the source (see [`Release::major`])
+    #[serde(rename = "majorVersion", default)]
+    pub major_version: Option<Major>,
     pub build: Build,
+    /// The release date, if known (e.g. not for products with a download template)
+    ///
+    /// Dates are parsed leniently (see [`Date::parse_lenient`]), and an unknown format is treated as a missing date.
+    #[serde(default, deserialize_with = "lenient_date")]
+    pub date: Option<Date>,
+    /// Whether a license is required to use this release (e.g. `false` for free EAPs)
+    #[serde(rename = "licenseRequired")]
+    pub license_required: Option<bool>,
     pub downloads: HashMap<String, Download>,
+
+    /// The link to the full release notes
+    #[serde(rename = "notesLink")]
+    pub notes_link: Option<String>,
+    /// A summary of what's new in this release, in HTML
+    pub whatsnew: Option<String>,
 }
 
 /// The deserialized download data from `JetBrains`' API. (This is a subset of the `Release` struct)
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Download {
+    /// The key of this download in the release (e.g. linuxARM64)
+    #[serde(skip)]
+    pub key: String,
+
     pub link: String,
+    /// The size of the archive, in bytes, or 0 if it is not known in advance
+    #[serde(default)]
     pub size: u64,
 
-    #[serde(rename = "checksumLink")]
+    /// The link to the SHA-256 checksum of the archive, or an empty string if there is none
+    #[serde(rename = "checksumLink", default)]
     pub checksum_link: String,
+    /// The SHA-256 checksum of the archive, for providers that give it inline instead of as a link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
-static ARCHITECTURES: &[&[&str]] = &[
-    &["x86_64", "amd64", "x86", "x64"],
-    &["aarch64", "arm64", "armv8l", "armv8b", "armv8", "armv9"],
-    &["i386", "x86_32", "x86_32b", "x86_32l"],
-];
+impl Download {
+    /// Returns the size of the archive, if it is known in advance.
+    #[must_use]
+    pub fn size(&self) -> Option<u64> {
+        Some(self.size).filter(|size| *size > 0)
+    }
+
+    /// Returns the expected checksum of the archive, if there is one.
+    ///
+    /// An inline checksum is preferred over a link, as it does not require another request.
+    #[must_use]
+    pub fn checksum(&self) -> Option<Checksum<'_>> {
+        match (&self.checksum, self.checksum_link.as_str()) {
+            (Some(checksum), _) => Some(Checksum::Sha256(checksum)),
+            (None, "") => None,
+            (None, link) => Some(Checksum::Link(link)),
+        }
+    }
+
+    /// Returns the archive format of the download from its link, if it is an archive that can be extracted.
+    #[must_use]
+    pub fn format(&self) -> Option<Format> {
+        crate::util::filename(&self.link)
+            .ok()
+            .and_then(Format::from_name)
+    }
+}
 
 impl Release {
+    /// Returns the major version of the release, derived from its version if the source did not give it.
+    #[must_use]
+    pub fn major(&self) -> Major {
+        self.major_version.clone()
+            .unwrap_or_else(|| self.version.major.clone())
+    }
+
+    /// Returns a summary of what's new in this release, converted to markdown.
+    ///
+    /// This returns `None` if the release does not provide any summary.
+    #[must_use]
+    pub fn notes(&self) -> Option<String> {
+        self.whatsnew.as_deref()
+            .map(crate::util::html_to_markdown)
+            .filter(|notes| !notes.is_empty())
+    }
+
     /// Returns the download for the current platform and architecture.
     ///
     /// # Errors
-    /// This function will return an error if the download is not found, or if the platform or architecture is not supported.
-    /// # Panics
-    /// This function will panic if the platform or architecture is not valid.
+    /// This function will return an error if the download is not found.
     pub fn download(&self) -> anyhow::Result<Download> {
-        let platform = std::env::consts::OS.to_string();
-        let arch = std::env::consts::ARCH.to_string();
-
-        let platform = match platform.as_str() {
-            "linux" => "linux",
-            "macos" => "mac",
-            "windows" => "windows",
-            _ => anyhow::bail!("Unsupported platform {}", platform),
-        };
-
-        // Find the list of architectures that match the given architecture
-        let archs = ARCHITECTURES
-            .iter()
-            .find(|archs| archs.contains(&arch.to_lowercase().as_str()));
-
-        if archs.is_none() {
-            bail!("Unsupported architecture {}", arch);
-        }
-        let archs = archs.unwrap();
+        self.download_for(&Target::host())
+    }
 
-        let arch_specific = archs
+    /// Returns the download for the given target platform and architecture.
+    ///
+    /// Only archives that can be extracted are considered (e.g. not the `.exe` or `.dmg` installers),
+    /// and the key of the chosen download is available through [`Download::key`].
+    ///
+    /// # Errors
+    /// This function will return an error if the download is not found, or if it is not an archive.
+    pub fn download_for(&self, target: &Target) -> anyhow::Result<Download> {
+        let candidates = target.keys()
             .iter()
-            .map(|arch| format!("{platform}_{arch}"))
-            .chain(archs.iter().map(|arch| format!("{platform}-{arch}")))
-            .chain(archs.iter().map(|arch| format!("{platform}{arch}")))
-            .find(|arch| {
+            .filter_map(|candidate| {
                 self.downloads
-                    .keys()
-                    .any(|key| key.eq_ignore_ascii_case(arch))
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(candidate))
             })
-            .and_then(|arch| self.downloads.get(arch.as_str()));
+            .collect::<Vec<_>>();
 
-        let platform_specific = self.downloads.get(platform);
+        if candidates.is_empty() {
+            anyhow::bail!("No download found for {target}");
+        }
 
-        let download = match (arch_specific, platform_specific) {
-            (Some(arch), _) => Some(arch),
-            (_, Some(platform)) => Some(platform),
-            _ => None,
-        };
+        candidates.iter()
+            .find(|(_, download)| download.format().is_some())
+            .map(|(key, download)| Download {
+                key: (*key).clone(),
+                ..(*download).clone()
+            })
+            .ok_or_else(|| {
+                let links = candidates.iter()
+                    .map(|(_, download)| download.link.as_str())
+                    .collect::<Vec<_>>();
 
-        download.cloned()
-            .ok_or_else(|| anyhow::anyhow!("No download found for platform {}", platform))
+                anyhow::anyhow!("No archive found for {target}, only {} which cannot be extracted", links.join(", "))
+            })
     }
-}
\ No newline at end of file
+}
+
+/// Deserializes a date leniently, as a missing or invalid date must not invalidate the whole release.
+fn lenient_date<'de, D>(deserializer: D) -> Result<Option<Date>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+    Ok(match value {
+        Some(serde_json::Value::String(s)) => {
+            let date = Date::parse_lenient(&s);
+            if date.is_none() {
+                crate::debug!("Ignoring invalid release date: {s}");
+            }
+            date
+        }
+        _ => None,
+    })
+}
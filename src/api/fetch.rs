@@ -1,20 +1,26 @@
-//! Module for fetching releases from `JetBrains`' API.
+//! Module for fetching releases of tools.
+//!
+//! Releases are fetched from the provider of each tool's product (see [`super::provider`]),
+//! which is `JetBrains`' API for most of them.
 
 use std::collections::HashMap;
 use anyhow::Context;
 
-use crate::tool::{Tool, release};
+use crate::tool::{Kind, Tool, release};
+use super::provider::Provider;
 use super::deserial::{Release, Download};
+use super::target::Target;
 
 /// The fetched data for a tool.
 #[derive(Debug, Clone)]
 pub struct Fetch {
     pub tool: Tool,
+    pub release: Release,
     pub download: Download,
 }
 
 
-/// Fetches the latest release of a tool from `JetBrains`' API.
+/// Fetches the latest release of a tool from its provider.
 ///
 /// # Errors
 /// This function will return an error if the request fails or if the response is not valid.
@@ -23,51 +29,66 @@ pub struct Fetch {
 /// # Panics
 /// This function will panic if the tool is not valid.
 pub fn release(tool: &Tool) -> anyhow::Result<Fetch> {
-    let latest = tool.version.is_none() && tool.build.is_none();
-    let release = tool.release.unwrap_or(release::Type::kind_default(tool.kind));
+    release_for(tool, &Target::host())
+}
+
+/// Fetches the latest release of a tool from its provider, for the given target platform and architecture.
+///
+/// # Errors
+/// This function will return an error if the request fails or if the response is not valid.
+/// It will also return an error if the tool is not found, if the release is not found,
+/// or if the release has no download for the target.
+pub fn release_for(tool: &Tool, target: &Target) -> anyhow::Result<Fetch> {
+    tool.kind.provider().get().release(tool, target)
+}
+
+/// Fetches the releases of many tools at once from their providers.
+///
+/// Tools are grouped by provider, which may batch their requests (e.g. a single request for each release type with `JetBrains`' API).
+/// The results are returned in the same order as the given tools, so that a failure for one tool does not affect the others.
+#[must_use]
+pub fn batch(tools: &[Tool]) -> Vec<anyhow::Result<Fetch>> {
+    batch_for(tools, &Target::host())
+}
 
-    let url = format!(
-        "https://data.services.jetbrains.com/products/releases?code={}&latest={}&type={}",
-        tool.kind.code(),
-        latest,
-        release.as_str()
-    );
+/// Fetches the releases of many tools at once from their providers, for the given target platform and architecture.
+///
+/// See [`batch`] for details.
+#[must_use]
+pub fn batch_for(tools: &[Tool], target: &Target) -> Vec<anyhow::Result<Fetch>> {
+    let mut groups: HashMap<Provider, Vec<usize>> = HashMap::new();
+    for (index, tool) in tools.iter().enumerate() {
+        groups.entry(tool.kind.provider()).or_default().push(index);
+    }
 
-    let releases = reqwest::blocking::get(url)
-        .with_context(|| format!("Failed to fetch releases for {}", tool.as_str()))?
-        .json::<HashMap<String, Vec<Release>>>()
-        .with_context(|| format!("Failed to parse releases for {}", tool.as_str()))?;
+    let mut results: Vec<Option<anyhow::Result<Fetch>>> = tools.iter().map(|_| None).collect();
 
-    let releases = releases.get(tool.kind.code())
-        .with_context(|| format!("No releases found for {}", tool.as_str()))?;
+    for (provider, indices) in groups {
+        let group = indices.iter()
+            .map(|&index| tools[index].clone())
+            .collect::<Vec<_>>();
 
-    let release = if latest {
-        releases.first()
-            .with_context(|| format!("No releases found for {}", tool.as_str()))?
-    } else {
-        releases.iter()
-            .find(|release| {
-                if (tool.version.is_some() && &release.version != tool.version.as_ref().unwrap()) ||
-                    (tool.build.is_some() && &release.build != tool.build.as_ref().unwrap()) {
-                    return false;
-                }
-                true
-            })
-            .with_context(|| format!("No release found for {}", tool.as_str()))?
-    };
+        for (index, result) in indices.into_iter().zip(provider.get().batch(&group, target)) {
+            results[index] = Some(result);
+        }
+    }
 
-    let download = release.download()
-        .with_context(|| format!("Failed to fetch download for {}", tool.as_str()))?;
+    results.into_iter()
+        .map(|result| result.expect("Every tool belongs to a group"))
+        .collect()
+}
 
-    let release = release.clone();
+/// Fetches all the releases of a tool kind from its provider, from the newest to the oldest.
+///
+/// If no release types are given, the default release type of the kind is used.
+///
+/// # Errors
+/// This function will return an error if the request fails or if the response is not valid.
+pub fn releases(kind: Kind, types: &[release::Type]) -> anyhow::Result<Vec<Release>> {
+    let default = [release::Type::kind_default(kind)];
+    let types = if types.is_empty() { &default } else { types };
 
-    Ok(Fetch {
-        tool: Tool::new(
-            tool.kind,
-            Some(release.version),
-            Some(release.build),
-            Some(release.release),
-        ),
-        download,
-    })
-}
\ No newline at end of file
+    kind.provider().get()
+        .releases(kind, types, false)
+        .with_context(|| format!("Failed to fetch releases for {kind}"))
+}
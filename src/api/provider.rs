@@ -0,0 +1,135 @@
+//! Release providers
+//!
+//! This module contains the sources that releases can be fetched from.
+//! Each product of the registry names its provider, and tools from any provider share the same install, link and
+//! desktop machinery once their release is fetched.
+
+pub mod data_services;
+pub mod android_studio;
+pub mod template;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use crate::tool::{Kind, Tool, Type};
+use super::{Fetch, Release, Target};
+
+pub use data_services::DataServices;
+pub use android_studio::AndroidStudio;
+pub use template::Template;
+
+/// A source of releases for tools.
+pub trait ReleaseProvider: Send + Sync {
+    /// Returns the name of the provider (e.g. data.services).
+    fn name(&self) -> &'static str;
+
+    /// Fetches the releases of a tool kind with the given release types, from the newest to the oldest.
+    ///
+    /// If `latest` is set, only the latest release of each type may be returned.
+    ///
+    /// # Errors
+    /// This function will return an error if the request fails or if the response is not valid.
+    fn releases(&self, kind: Kind, types: &[Type], latest: bool) -> anyhow::Result<Vec<Release>>;
+
+    /// Fetches the release matching a tool, for the given target platform and architecture.
+    ///
+    /// By default, the release is selected from all the releases of the tool kind (see [`select`]).
+    ///
+    /// # Errors
+    /// This function will return an error if the request fails, if no release matches the tool,
+    /// or if the release has no download for the target.
+    fn release(&self, tool: &Tool, target: &Target) -> anyhow::Result<Fetch> {
+        let release = tool.release.unwrap_or(tool.kind.channel());
+
+        let releases = self.releases(tool.kind, &[release], is_latest(tool))
+            .with_context(|| format!("Failed to fetch releases for {}", tool.as_str()))?;
+
+        select(tool, &releases, target)
+    }
+
+    /// Fetches the releases matching many tools at once, in the same order as the given tools.
+    ///
+    /// By default, each release is fetched on its own.
+    fn batch(&self, tools: &[Tool], target: &Target) -> Vec<anyhow::Result<Fetch>> {
+        tools.iter()
+            .map(|tool| self.release(tool, target))
+            .collect()
+    }
+}
+
+/// The provider of a product, as named in the product registry.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    /// `JetBrains`' releases API (see [`DataServices`])
+    #[default]
+    DataServices,
+    /// Android Studio's release feed (see [`AndroidStudio`])
+    AndroidStudio,
+    /// The download URL template of the product (see [`Template`])
+    Template,
+}
+
+impl Provider {
+    /// Returns the implementation of the provider.
+    #[must_use]
+    pub fn get(&self) -> &'static dyn ReleaseProvider {
+        match self {
+            Self::DataServices => &DataServices,
+            Self::AndroidStudio => &AndroidStudio,
+            Self::Template => &Template,
+        }
+    }
+
+    /// Returns whether this is the default provider.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Returns whether only the latest release of a tool is needed.
+#[must_use]
+pub fn is_latest(tool: &Tool) -> bool {
+    tool.version.is_none() && tool.build.is_none() && tool.constraint.is_none()
+}
+
+/// Selects the release matching a tool from the releases of its kind, and its download for the given target.
+///
+/// The latest release is selected if the tool has no version, build nor constraint.
+/// Otherwise, the newest release matching all of them is selected.
+///
+/// # Errors
+/// This function will return an error if no release matches the tool, or if the release has no download for the target.
+pub fn select(tool: &Tool, releases: &[Release], target: &Target) -> anyhow::Result<Fetch> {
+    let release = if is_latest(tool) {
+        releases.first()
+            .with_context(|| format!("No releases found for {}", tool.as_str()))?
+    } else {
+        releases.iter()
+            .find(|release| {
+                if (tool.version.is_some() && &release.version != tool.version.as_ref().unwrap()) ||
+                    (tool.build.is_some() && &release.build != tool.build.as_ref().unwrap()) {
+                    return false;
+                }
+                tool.constraint.as_ref()
+                    .map_or(true, |constraint| constraint.matches(Some(&release.version), Some(&release.build)))
+            })
+            .with_context(|| format!("No release found for {}", tool.as_str()))?
+    };
+
+    let download = release.download_for(target)
+        .with_context(|| format!("Failed to fetch download for {}", tool.as_str()))?;
+
+    let release = release.clone();
+
+    Ok(Fetch {
+        tool: Tool::new(
+            tool.kind,
+            Some(release.version.clone()),
+            Some(release.build.clone()),
+            Some(release.release),
+        ),
+        release,
+        download,
+    })
+}
@@ -0,0 +1,192 @@
+//! Android Studio's release feed
+//!
+//! This module contains the provider for Android Studio, which is based on the `IntelliJ` platform but released by
+//! Google, and is therefore not in `JetBrains`' releases API. Its releases are listed in a feed published by `JetBrains`,
+//! configured with [`Variable::AndroidStudioFeed`].
+
+use std::collections::HashMap;
+use anyhow::Context;
+use serde::Deserialize;
+use crate::archive::Format;
+use crate::env::Variable;
+use crate::tool::{Build, Kind, Version, release};
+use crate::api::{cache, Date, Download, Release};
+use super::ReleaseProvider;
+
+/// Every release type, as the whole feed is cached at once.
+const TYPES: &[release::Type] = &[release::Type::Release, release::Type::EAP, release::Type::Preview];
+
+/// Fetches releases from Android Studio's release feed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AndroidStudio;
+
+impl ReleaseProvider for AndroidStudio {
+    fn name(&self) -> &'static str {
+        "android-studio"
+    }
+
+    fn releases(&self, kind: Kind, types: &[release::Type], _latest: bool) -> anyhow::Result<Vec<Release>> {
+        let feed = feed(kind)?;
+
+        let mut releases = feed.content.item
+            .into_iter()
+            .filter_map(Item::into_release)
+            .filter(|release| types.contains(&release.release))
+            .collect::<Vec<_>>();
+
+        releases.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| b.build.cmp(&a.build)));
+        Ok(releases)
+    }
+}
+
+/// The release feed of Android Studio.
+#[derive(Deserialize, Debug)]
+struct Feed {
+    content: Content,
+}
+
+#[derive(Deserialize, Debug)]
+struct Content {
+    #[serde(default)]
+    item: Vec<Item>,
+}
+
+/// A release in the feed.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    /// The full version (e.g. 2024.1.1.12)
+    version: String,
+    /// The full build number (e.g. AI-241.15989.150.2411.11948838)
+    build: String,
+    /// The release channel (e.g. Release, Beta, Canary)
+    channel: String,
+    /// The release date (e.g. June 26, 2024)
+    date: String,
+    #[serde(default)]
+    download: Vec<Link>,
+}
+
+/// A download of a release in the feed.
+#[derive(Deserialize, Debug)]
+struct Link {
+    link: String,
+    /// The SHA-256 checksum of the archive
+    checksum: Option<String>,
+}
+
+impl Item {
+    /// Converts the item to a release, skipping the ones that cannot be parsed or installed.
+    ///
+    /// The full build number is used (without its product code), as found in the `product-info.json` of the tool.
+    fn into_release(self) -> Option<Release> {
+        let release = match self.channel.to_lowercase().as_str() {
+            "release" | "patch" => release::Type::Release,
+            "beta" | "rc" => release::Type::Preview,
+            "canary" => release::Type::EAP,
+            _ => return None,
+        };
+
+        let Ok(version) = self.version.parse::<Version>() else {
+            crate::debug!("Skipping Android Studio {} with an unsupported version", self.version);
+            return None;
+        };
+        let Ok(build) = self.build.trim_start_matches("AI-").parse::<Build>() else {
+            crate::debug!("Skipping Android Studio {} with an unsupported build {}", self.version, self.build);
+            return None;
+        };
+
+        let downloads = self.download
+            .into_iter()
+            .filter_map(|link| {
+                let key = key(&link.link)?;
+                Some((key.clone(), Download {
+                    key,
+                    link: link.link,
+                    size: 0,
+                    checksum_link: String::new(),
+                    checksum: link.checksum.filter(|checksum| !checksum.is_empty()),
+                }))
+            })
+            .collect::<HashMap<_, _>>();
+
+        Some(Release {
+            release,
+            printable_release: Some(self.channel),
+            major_version: Some(version.major.clone()),
+            version,
+            build,
+            date: parse_date(&self.date),
+            license_required: Some(false),
+            downloads,
+            notes_link: None,
+            whatsnew: None,
+        })
+    }
+}
+
+/// Fetches the release feed, from the cache when possible.
+///
+/// The feed is cached under its URL, so that changing the feed URL takes effect right away.
+fn feed(kind: Kind) -> anyhow::Result<Feed> {
+    let url = Variable::AndroidStudioFeed.get::<String>();
+
+    let value = match cache::get(&url, kind.code(), TYPES, false) {
+        Some(value) => value,
+        None if Variable::Offline.get_bool() => anyhow::bail!("No cached releases found for {kind}, cannot fetch them while offline"),
+        None => {
+            let client = crate::http::blocking()?;
+
+            let value = crate::http::retry(|| {
+                client.get(&url)
+                    .send()
+                    .and_then(reqwest::blocking::Response::error_for_status)
+                    .with_context(|| format!("Failed to fetch {url}"))?
+                    .json::<serde_json::Value>()
+                    .with_context(|| format!("Failed to parse {url}"))
+            })?;
+
+            if let Err(err) = cache::put(&url, kind.code(), TYPES, false, &value) {
+                crate::debug!("Failed to cache releases for {}: {err:#}", kind.code());
+            }
+            value
+        }
+    };
+
+    serde_json::from_value(value)
+        .with_context(|| format!("Failed to parse the release feed of {kind}"))
+}
+
+/// Returns the download key of an archive from its name (e.g. `android-studio-2024.1.1.12-linux.tar.gz` is linux).
+///
+/// The keys are the ones used by `JetBrains`' API, so that targets match them the same way.
+fn key(link: &str) -> Option<String> {
+    let name = link.rsplit('/').next()?.to_lowercase();
+    let format = Format::from_name(&name)?;
+    let stem = format.extensions()
+        .iter()
+        .find_map(|extension| name.strip_suffix(extension))?;
+
+    Some(match stem.rsplit('-').next()? {
+        "mac_arm" => "mac_arm64".to_string(),
+        platform => platform.to_string(),
+    })
+}
+
+/// Parses a date as written in the feed (e.g. June 26, 2024).
+fn parse_date(date: &str) -> Option<Date> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june",
+        "july", "august", "september", "october", "november", "december",
+    ];
+
+    let (month, rest) = date.trim().split_once(' ')?;
+    let (day, year) = rest.split_once(',')?;
+
+    let month = MONTHS.iter().position(|name| name.eq_ignore_ascii_case(month))?;
+    Date::new(
+        year.trim().parse().ok()?,
+        u8::try_from(month + 1).ok()?,
+        day.trim().parse().ok()?,
+    ).ok()
+}
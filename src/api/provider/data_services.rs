@@ -0,0 +1,158 @@
+//! `JetBrains`' releases API
+//!
+//! This module contains the provider for the data.services API (or any mirror of it), which serves every `JetBrains` product.
+
+use std::collections::HashMap;
+use anyhow::Context;
+use crate::env::Variable;
+use crate::tool::{Kind, Tool, release};
+use crate::api::{cache, Fetch, Release, Target};
+use super::{ReleaseProvider, is_latest, select};
+
+/// Fetches releases from `JetBrains`' releases API, configured with [`Variable::ApiUrl`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataServices;
+
+impl ReleaseProvider for DataServices {
+    fn name(&self) -> &'static str {
+        "data.services"
+    }
+
+    fn releases(&self, kind: Kind, types: &[release::Type], latest: bool) -> anyhow::Result<Vec<Release>> {
+        let mut releases = request(&[kind.code()], latest, types)?;
+
+        match releases.remove(kind.code()) {
+            Some(releases) => Ok(releases),
+            None if Variable::Offline.get_bool() => anyhow::bail!("No cached releases found for {kind}, cannot fetch them while offline"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Tools are grouped by release type, and a single request is made for each group.
+    fn batch(&self, tools: &[Tool], target: &Target) -> Vec<anyhow::Result<Fetch>> {
+        let mut groups: HashMap<release::Type, Vec<usize>> = HashMap::new();
+        for (index, tool) in tools.iter().enumerate() {
+            let release = tool.release.unwrap_or(tool.kind.channel());
+            groups.entry(release).or_default().push(index);
+        }
+
+        let mut results: Vec<Option<anyhow::Result<Fetch>>> = tools.iter().map(|_| None).collect();
+
+        for (release, indices) in groups {
+            let mut codes = indices.iter()
+                .map(|&index| tools[index].kind.code())
+                .collect::<Vec<_>>();
+            codes.sort_unstable(); codes.dedup();
+
+            let latest = indices.iter().all(|&index| is_latest(&tools[index]));
+
+            match request(&codes, latest, &[release]) {
+                Ok(releases) => {
+                    for index in indices {
+                        let tool = &tools[index];
+                        results[index] = Some(match releases.get(tool.kind.code()) {
+                            Some(releases) => select(tool, releases, target),
+                            None if Variable::Offline.get_bool() => Err(anyhow::anyhow!("No cached releases found for {}, cannot fetch them while offline", tool.as_str())),
+                            None => Err(anyhow::anyhow!("No releases found for {}", tool.as_str())),
+                        });
+                    }
+                }
+                Err(err) => {
+                    // The same error is reported for each tool, as they all depend on the same request
+                    let message = format!("{err:#}");
+                    for index in indices {
+                        results[index] = Some(Err(anyhow::anyhow!("{message}"))
+                            .with_context(|| format!("Failed to fetch releases for {}", tools[index].as_str())));
+                    }
+                }
+            }
+        }
+
+        results.into_iter()
+            .map(|result| result.expect("Every tool belongs to a group"))
+            .collect()
+    }
+}
+
+/// Requests the releases of the given product codes from `JetBrains`' API.
+///
+/// Releases are taken from the cache when possible, and only the missing product codes are requested.
+/// When running offline, missing product codes are left out of the result.
+/// The links of all downloads are resolved against the API base URL.
+fn request(codes: &[&str], latest: bool, types: &[release::Type]) -> anyhow::Result<HashMap<String, Vec<Release>>> {
+    let source = Variable::ApiUrl.get::<String>();
+    let mut values = HashMap::new();
+    let mut missing = vec![];
+
+    for code in codes {
+        match cache::get(&source, code, types, latest) {
+            Some(value) => {
+                values.insert((*code).to_string(), value);
+            }
+            None => missing.push(*code),
+        }
+    }
+
+    if !missing.is_empty() && !Variable::Offline.get_bool() {
+        let fetched = download(&missing, latest, types)?;
+
+        for (code, value) in fetched {
+            if let Err(err) = cache::put(&source, &code, types, latest, &value) {
+                crate::debug!("Failed to cache releases for {code}: {err:#}");
+            }
+            values.insert(code, value);
+        }
+    }
+
+    let mut releases = HashMap::new();
+    for (code, value) in values {
+        // A single invalid release (e.g. from a mirror with a slightly different format) must not hide the others
+        let mut list = serde_json::from_value::<Vec<serde_json::Value>>(value)
+            .with_context(|| format!("Failed to parse releases for {code}"))?
+            .into_iter()
+            .filter_map(|release| match serde_json::from_value::<Release>(release) {
+                Ok(release) => Some(release),
+                Err(err) => {
+                    crate::debug!("Skipping invalid release of {code}: {err}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for download in list.iter_mut().flat_map(|release| release.downloads.values_mut()) {
+            download.link = crate::api::resolve(&download.link)?;
+            if !download.checksum_link.is_empty() {
+                download.checksum_link = crate::api::resolve(&download.checksum_link)?;
+            }
+        }
+
+        releases.insert(code, list);
+    }
+
+    Ok(releases)
+}
+
+/// Downloads the raw releases of the given product codes from `JetBrains`' API.
+fn download(codes: &[&str], latest: bool, types: &[release::Type]) -> anyhow::Result<HashMap<String, serde_json::Value>> {
+    let types = types.iter()
+        .map(release::Type::as_str)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut url = crate::api::endpoint("products/releases")?;
+    url.query_pairs_mut()
+        .append_pair("code", &codes.join(","))
+        .append_pair("latest", &latest.to_string())
+        .append_pair("type", &types);
+
+    let client = crate::http::blocking()?;
+
+    crate::http::retry(|| {
+        client.get(url.clone())
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .with_context(|| format!("Failed to fetch {url}"))?
+            .json::<HashMap<String, serde_json::Value>>()
+            .with_context(|| format!("Failed to parse {url}"))
+    })
+}
@@ -0,0 +1,90 @@
+//! Download URL templates
+//!
+//! This module contains the provider for products that are not in any release feed, such as internal distributions.
+//! Their download is built from the URL template of the product (see [`crate::tool::Product::render`]).
+
+use std::collections::HashMap;
+use anyhow::Context;
+use crate::tool::{Build, Kind, Product, Tool, Version, release};
+use crate::api::{Download, Fetch, Release, Target};
+use super::ReleaseProvider;
+
+/// Builds releases from the download URL template of a product, without any request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Template;
+
+impl ReleaseProvider for Template {
+    fn name(&self) -> &'static str {
+        "template"
+    }
+
+    /// Only the default release of the product is known.
+    fn releases(&self, kind: Kind, _types: &[release::Type], _latest: bool) -> anyhow::Result<Vec<Release>> {
+        let fetch = self.release(&Tool::from_kind(kind), &Target::host())?;
+        Ok(vec![fetch.release])
+    }
+
+    /// The version and build default to the ones of the product. A build is required along with a version,
+    /// as it is part of the tool's directory name and cannot be known from the template.
+    /// The release date is unknown, as templates have no release feed.
+    fn release(&self, tool: &Tool, target: &Target) -> anyhow::Result<Fetch> {
+        let product = tool.kind.product();
+        let template = product.download.as_deref()
+            .with_context(|| format!("{} has no download template", tool.kind))?;
+
+        let version = match (&tool.version, &product.version) {
+            (Some(version), _) => version.clone(),
+            (None, Some(version)) => version.parse::<Version>()?,
+            (None, None) => anyhow::bail!(
+                "No version given for {}, which has no default version (e.g. {}_2024.1)",
+                tool.as_str(),
+                tool.kind.as_str(),
+            ),
+        };
+        let build = match (&tool.build, &product.build) {
+            (Some(build), _) => build.clone(),
+            (None, Some(build)) if tool.version.is_none() => build.parse::<Build>()?,
+            _ => anyhow::bail!(
+                "No build given for {}, which is required for products with a download template (e.g. {}_{version}-241.14494)",
+                tool.as_str(),
+                tool.kind.as_str(),
+            ),
+        };
+
+        if let Some(constraint) = &tool.constraint {
+            if !constraint.matches(Some(&version), Some(&build)) {
+                anyhow::bail!("No release found for {}, only {version} ({build}) is known", tool.as_str());
+            }
+        }
+
+        let download = Download {
+            key: target.keys().remove(0),
+            link: crate::api::resolve(&Product::render(template, &version, &build, target))?,
+            size: 0,
+            checksum_link: product.checksum.as_deref()
+                .map(|checksum| crate::api::resolve(&Product::render(checksum, &version, &build, target)))
+                .transpose()?
+                .unwrap_or_default(),
+            checksum: None,
+        };
+
+        let release = Release {
+            release: tool.release.unwrap_or(product.channel),
+            printable_release: None,
+            major_version: Some(version.major.clone()),
+            version: version.clone(),
+            build: build.clone(),
+            date: None,
+            license_required: None,
+            downloads: HashMap::from([(download.key.clone(), download.clone())]),
+            notes_link: None,
+            whatsnew: None,
+        };
+
+        Ok(Fetch {
+            tool: Tool::new(tool.kind, Some(version), Some(build), Some(release.release)),
+            release,
+            download,
+        })
+    }
+}
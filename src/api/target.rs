@@ -0,0 +1,113 @@
+//! Target platforms and architectures
+//!
+//! This module contains the target for which a tool is downloaded, which may differ from the current host.
+
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Known aliases for each architecture, the first one being the canonical name.
+static ARCHITECTURES: &[&[&str]] = &[
+    &["x86_64", "amd64", "x64"],
+    &["aarch64", "arm64", "armv8l", "armv8b", "armv8", "armv9"],
+    &["i386", "x86", "i686", "x86_32", "x86_32b", "x86_32l"],
+];
+
+/// Download keys of `JetBrains`' API that do not follow the platform and architecture naming, by platform and architecture.
+static SPECIAL_KEYS: &[(&str, &str, &[&str])] = &[
+    ("mac", "aarch64", &["macM1"]),
+    ("windows", "x86_64", &["windowsZip"]),
+    ("windows", "aarch64", &["windowsZipARM64", "windowsARM64"]),
+];
+
+/// Known aliases for each platform, the first one being the name used by `JetBrains`' API.
+static PLATFORMS: &[&[&str]] = &[
+    &["linux"],
+    &["mac", "macos", "darwin", "osx"],
+    &["windows", "win"],
+];
+
+/// A target platform and architecture (e.g. linux x86_64)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[readonly::make]
+pub struct Target {
+    /// The platform, as named by `JetBrains`' API (e.g. linux, mac, windows)
+    pub platform: String,
+    /// The canonical architecture name (e.g. `x86_64`, `aarch64`)
+    pub arch: String,
+}
+
+impl Target {
+    /// Creates a new target from a platform and architecture.
+    ///
+    /// Aliases are accepted (e.g. `macos` or `arm64`), and are normalized to their canonical name.
+    ///
+    /// # Errors
+    /// This function will return an error if the platform or architecture is not supported.
+    pub fn new(platform: &str, arch: &str) -> anyhow::Result<Self> {
+        let platform = Self::family(PLATFORMS, platform)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported platform {platform}"))?[0];
+        let arch = Self::family(ARCHITECTURES, arch)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported architecture {arch}"))?[0];
+
+        Ok(Self {
+            platform: platform.to_string(),
+            arch: arch.to_string(),
+        })
+    }
+
+    /// Returns the target of the current host.
+    ///
+    /// # Panics
+    /// This function will panic if the current platform or architecture is not supported.
+    #[must_use]
+    pub fn host() -> Self {
+        Self::new(std::env::consts::OS, std::env::consts::ARCH)
+            .expect("The current platform is not supported")
+    }
+
+    /// Returns whether this target can run on the current host.
+    #[must_use]
+    pub fn is_host(&self) -> bool {
+        self == &Self::host()
+    }
+
+    /// Returns the download keys that may be used by `JetBrains`' API for this target, by order of preference.
+    ///
+    /// Keys specific to `JetBrains`' API (e.g. `macM1` or `windowsZip`) come first,
+    /// and archives without an architecture in their key (e.g. `linux`) are built for `x86_64`.
+    #[must_use]
+    pub fn keys(&self) -> Vec<String> {
+        let platform = self.platform.as_str();
+        let archs = Self::family(ARCHITECTURES, &self.arch).unwrap_or_default();
+
+        let special = SPECIAL_KEYS
+            .iter()
+            .filter(|(special_platform, special_arch, _)| *special_platform == platform && *special_arch == self.arch)
+            .flat_map(|(.., keys)| keys.iter().map(ToString::to_string));
+
+        let mut keys = special
+            .chain(archs.iter().map(|arch| format!("{platform}_{arch}")))
+            .chain(archs.iter().map(|arch| format!("{platform}-{arch}")))
+            .chain(archs.iter().map(|arch| format!("{platform}{arch}")))
+            .collect::<Vec<_>>();
+
+        if self.arch == ARCHITECTURES[0][0] {
+            keys.push(platform.to_string());
+        }
+
+        keys
+    }
+
+    fn family(families: &'static [&'static [&'static str]], name: &str) -> Option<&'static [&'static str]> {
+        families
+            .iter()
+            .find(|family| family.contains(&name.to_lowercase().as_str()))
+            .copied()
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.platform, self.arch)
+    }
+}
@@ -0,0 +1,10 @@
+//! Module for handling downloaded archives.
+//!
+//! This module contains the supported archive formats, their hardened extraction, and the cache of archives,
+//! which allows reinstalling a tool without downloading it again.
+
+pub mod cache;
+pub mod extract;
+pub mod format;
+
+pub use format::Format;
@@ -0,0 +1,230 @@
+//! Module for caching downloaded archives.
+//!
+//! Archives are stored by their SHA-256 checksum (as given by the releases API), so that reinstalling a build does not
+//! require downloading it again, even on another machine sharing the same cache directory.
+//! The cache is bounded in size, and the least recently used archives are evicted first.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::Context;
+use crate::env::Variable;
+
+/// An archive stored in the cache.
+#[derive(Debug, Clone)]
+#[readonly::make]
+pub struct Entry {
+    /// The SHA-256 checksum of the archive
+    pub sha256: String,
+    /// The original filename of the archive (e.g. ideaIU-2024.1.tar.gz)
+    pub filename: String,
+    /// The path of the archive in the cache
+    pub path: PathBuf,
+    /// The size of the archive, in bytes
+    pub size: u64,
+    /// The last time the archive was stored or used
+    pub used: SystemTime,
+}
+
+/// Returns whether the archive cache is enabled (a non-zero size).
+#[must_use]
+pub fn is_enabled() -> bool {
+    capacity() > 0
+}
+
+/// Returns the maximum size of the archive cache, in bytes.
+#[must_use]
+pub fn capacity() -> u64 {
+    Variable::ArchiveCacheSize.get_u64().saturating_mul(1_000_000)
+}
+
+/// Returns the directory of the archive cache.
+#[must_use]
+pub fn directory() -> PathBuf {
+    Variable::ArchiveCacheDirectory.get::<PathBuf>()
+}
+
+/// Returns the cached archive with the given checksum, if any.
+///
+/// The archive is marked as used, so that it is evicted last.
+#[must_use]
+pub fn get(sha256: &str) -> Option<PathBuf> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let entry = list()
+        .ok()?
+        .into_iter()
+        .find(|entry| entry.sha256 == sha256)?;
+
+    if let Err(err) = touch(&entry.path) {
+        crate::debug!("Failed to mark {} as used: {err:#}", entry.path.display());
+    }
+
+    crate::debug!("Using cached archive {}", entry.path.display());
+    Some(entry.path)
+}
+
+/// Returns the cached archive that would be used to install a download, if any, without marking it as used.
+///
+/// Archives are looked up by checksum when it is known beforehand, and otherwise by the filename of their URL,
+/// which is only an estimate (e.g. of the space needed to install it), as installs only use archives by checksum.
+#[must_use]
+pub fn find(sha256: Option<&str>, url: &str) -> Option<Entry> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let filename = crate::util::filename(url).ok()?;
+    list()
+        .ok()?
+        .into_iter()
+        .find(|entry| sha256.map_or(entry.filename == filename, |sha256| entry.sha256.eq_ignore_ascii_case(sha256.trim())))
+}
+
+/// Moves an archive to the cache, then evicts the least recently used archives if the cache is too large.
+///
+/// The archive must have been verified against its checksum beforehand.
+/// Nothing is stored if the cache is disabled, and the archive is left untouched.
+///
+/// # Errors
+/// This function will return an error if the archive cannot be moved to the cache, or if eviction fails.
+pub fn put(sha256: &str, filename: &str, archive: &Path) -> anyhow::Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+
+    let directory = directory();
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+    let path = directory.join(format!("{sha256}_{filename}"));
+
+    // The cache may be on another filesystem (e.g. a shared mount), in which case the archive must be copied
+    if std::fs::rename(archive, &path).is_err() {
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        if let Err(err) = std::fs::copy(archive, &temp) {
+            // Do not leave a partial copy behind (e.g. when the disk is full)
+            let _ = std::fs::remove_file(&temp);
+            return Err(err).with_context(|| format!("Failed to copy {} to {}", archive.display(), temp.display()));
+        }
+        std::fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to move {} to {}", temp.display(), path.display()))?;
+        std::fs::remove_file(archive)
+            .with_context(|| format!("Failed to remove {}", archive.display()))?;
+    }
+    touch(&path)?;
+
+    crate::debug!("Cached archive to {}", path.display());
+
+    prune()?;
+    Ok(())
+}
+
+/// Lists all cached archives, most recently used first.
+///
+/// # Errors
+/// This function will return an error if the cache directory cannot be read.
+pub fn list() -> anyhow::Result<Vec<Entry>> {
+    let directory = directory();
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&directory)
+        .with_context(|| format!("Failed to read {}", directory.display()))?;
+
+    let mut archives = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read {}", directory.display()))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // Skip temporary files and anything that was not stored by us
+        let Some((sha256, filename)) = name.split_once('_') else {
+            continue;
+        };
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) || name.ends_with(".tmp") {
+            continue;
+        }
+
+        let metadata = entry.metadata()
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        archives.push(Entry {
+            sha256: sha256.to_string(),
+            filename: filename.to_string(),
+            path: entry.path(),
+            size: metadata.len(),
+            used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+
+    archives.sort_by_key(|entry| std::cmp::Reverse(entry.used));
+    Ok(archives)
+}
+
+/// Evicts the least recently used archives until the cache fits in its maximum size.
+///
+/// Returns the evicted archives.
+///
+/// # Errors
+/// This function will return an error if the cache cannot be read, or if an archive cannot be removed.
+pub fn prune() -> anyhow::Result<Vec<Entry>> {
+    let capacity = capacity();
+
+    let mut archives = list()?;
+    let mut size = archives.iter().map(|entry| entry.size).sum::<u64>();
+
+    let mut evicted = Vec::new();
+    while size > capacity {
+        let Some(entry) = archives.pop() else {
+            break;
+        };
+
+        remove(&entry)?;
+        crate::debug!("Evicted cached archive {}", entry.path.display());
+
+        size -= entry.size;
+        evicted.push(entry);
+    }
+
+    Ok(evicted)
+}
+
+/// Removes all cached archives.
+///
+/// Returns the removed archives.
+///
+/// # Errors
+/// This function will return an error if the cache cannot be read, or if an archive cannot be removed.
+pub fn clear() -> anyhow::Result<Vec<Entry>> {
+    let archives = list()?;
+    for entry in &archives {
+        remove(entry)?;
+    }
+
+    Ok(archives)
+}
+
+/// Removes an archive from the cache (e.g. if it turned out to be corrupted).
+///
+/// # Errors
+/// This function will return an error if the archive cannot be removed.
+pub fn remove(entry: &Entry) -> anyhow::Result<()> {
+    std::fs::remove_file(&entry.path)
+        .with_context(|| format!("Failed to remove {}", entry.path.display()))
+}
+
+/// Marks an archive as used, by updating its modification time.
+fn touch(path: &Path) -> anyhow::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+        .with_context(|| format!("Failed to update {}", path.display()))
+}
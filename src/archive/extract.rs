@@ -0,0 +1,317 @@
+//! Archive extraction
+//!
+//! This module contains a hardened extraction of tarballs and zip archives.
+//! The single leading directory of each entry is stripped while streaming, and entries that could write outside of
+//! the install root (absolute paths, `..`, or symlinks pointing outside of it) are rejected and reported.
+//! Permissions (e.g. executable bits) and symlinks within the archive are kept.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read, Seek};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use anyhow::Context;
+
+/// The outcome of an extraction.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The number of entries that were extracted
+    pub extracted: usize,
+    /// The entries that were rejected, and why
+    pub rejected: Vec<Rejection>,
+}
+
+/// An entry that was rejected during extraction.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// The path of the entry in the archive
+    pub path: String,
+    /// The reason why the entry was rejected
+    pub reason: String,
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.path, self.reason)
+    }
+}
+
+/// Extracts a tarball to a folder.
+///
+/// The reader must already be decompressed (see [`super::Format::decoder`]).
+///
+/// # Errors
+/// This function will return an error if the tarball cannot be read, or if an entry cannot be written.
+/// Unsafe entries are not errors, they are skipped and reported instead.
+pub fn tar(reader: impl Read, folder: &Path) -> anyhow::Result<Report> {
+    let mut extractor = Extractor::new(folder)?;
+    let mut archive = tar::Archive::new(reader);
+
+    let entries = archive.entries()
+        .with_context(|| "Failed to read tarball")?;
+    for entry in entries {
+        let mut entry = entry
+            .with_context(|| "Failed to read tarball entry")?;
+        let path = entry.path()
+            .with_context(|| "Failed to read tarball entry path")?
+            .into_owned();
+
+        let header = entry.header();
+        let mode = header.mode().ok();
+        let modified = header.mtime().ok().map(|mtime| SystemTime::UNIX_EPOCH + Duration::from_secs(mtime));
+
+        let kind = match header.entry_type() {
+            tar::EntryType::Directory => Entry::Directory,
+            tar::EntryType::Regular | tar::EntryType::Continuous => Entry::File,
+            tar::EntryType::Symlink | tar::EntryType::Link => {
+                let Some(target) = entry.link_name().with_context(|| "Failed to read tarball link")? else {
+                    extractor.reject(&path, "link without a target");
+                    continue;
+                };
+                let target = target.into_owned();
+
+                if header.entry_type() == tar::EntryType::Symlink {
+                    Entry::Symlink(target)
+                } else {
+                    Entry::HardLink(target)
+                }
+            }
+            tar::EntryType::XGlobalHeader | tar::EntryType::XHeader => continue,
+            kind => {
+                extractor.reject(&path, &format!("unsupported entry type {kind:?}"));
+                continue;
+            }
+        };
+
+        extractor.extract(&path, &kind, mode, modified, &mut entry)?;
+    }
+
+    Ok(extractor.report)
+}
+
+/// Extracts a zip archive to a folder.
+///
+/// # Errors
+/// This function will return an error if the archive cannot be read, or if an entry cannot be written.
+/// Unsafe entries are not errors, they are skipped and reported instead.
+pub fn zip(reader: impl Read + Seek, folder: &Path) -> anyhow::Result<Report> {
+    let mut extractor = Extractor::new(folder)?;
+    let mut archive = zip::ZipArchive::new(reader)
+        .with_context(|| "Failed to read zip archive")?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)
+            .with_context(|| "Failed to read zip archive entry")?;
+        let path = PathBuf::from(file.name());
+        let mode = file.unix_mode();
+
+        let kind = if file.is_dir() {
+            Entry::Directory
+        } else if file.is_symlink() {
+            let mut target = String::new();
+            file.read_to_string(&mut target)
+                .with_context(|| format!("Failed to read zip archive link {}", path.display()))?;
+            Entry::Symlink(PathBuf::from(target))
+        } else {
+            Entry::File
+        };
+
+        extractor.extract(&path, &kind, mode, None, &mut file)?;
+    }
+
+    Ok(extractor.report)
+}
+
+/// The kind of an archive entry.
+enum Entry {
+    Directory,
+    File,
+    Symlink(PathBuf),
+    HardLink(PathBuf),
+}
+
+/// Writes archive entries to an install root, keeping track of rejected entries.
+struct Extractor {
+    root: PathBuf,
+    report: Report,
+}
+
+impl Extractor {
+    fn new(folder: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(folder)
+            .with_context(|| format!("Failed to create {}", folder.display()))?;
+
+        Ok(Self {
+            root: folder.to_path_buf(),
+            report: Report::default(),
+        })
+    }
+
+    fn reject(&mut self, path: &Path, reason: &str) {
+        crate::warn!("Rejected archive entry {}: {reason}", path.display());
+
+        self.report.rejected.push(Rejection {
+            path: path.display().to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn extract(
+        &mut self,
+        path: &Path,
+        kind: &Entry,
+        mode: Option<u32>,
+        modified: Option<SystemTime>,
+        content: &mut dyn Read,
+    ) -> anyhow::Result<()> {
+        let destination = match self.destination(path, matches!(kind, Entry::Directory)) {
+            Ok(Some(destination)) => destination,
+            Ok(None) => return Ok(()),
+            Err(reason) => {
+                self.reject(path, &reason);
+                return Ok(());
+            }
+        };
+
+        if let Err(reason) = self.check_parents(&destination) {
+            self.reject(path, &reason);
+            return Ok(());
+        }
+
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        // Later entries replace earlier ones, as with tar itself
+        if !matches!(kind, Entry::Directory) {
+            if let Ok(metadata) = destination.symlink_metadata() {
+                if metadata.is_dir() {
+                    std::fs::remove_dir_all(&destination)
+                } else {
+                    std::fs::remove_file(&destination)
+                }.with_context(|| format!("Failed to replace {}", destination.display()))?;
+            }
+        }
+
+        match kind {
+            Entry::Directory => {
+                std::fs::create_dir_all(&destination)
+                    .with_context(|| format!("Failed to create {}", destination.display()))?;
+
+                // Directories must stay writable, so that their content can be extracted
+                let mode = mode.map_or(0o755, |mode| mode & 0o777) | 0o700;
+                std::fs::set_permissions(&destination, std::fs::Permissions::from_mode(mode))
+                    .with_context(|| format!("Failed to set permissions of {}", destination.display()))?;
+            }
+            Entry::File => {
+                let mut file = File::create(&destination)
+                    .with_context(|| format!("Failed to create {}", destination.display()))?;
+                io::copy(content, &mut file)
+                    .with_context(|| format!("Failed to write {}", destination.display()))?;
+
+                // Keep executable bits, but never setuid, setgid or sticky bits
+                let mode = mode.map_or(0o644, |mode| mode & 0o777);
+                file.set_permissions(std::fs::Permissions::from_mode(mode))
+                    .with_context(|| format!("Failed to set permissions of {}", destination.display()))?;
+
+                if let Some(modified) = modified {
+                    file.set_modified(modified)
+                        .with_context(|| format!("Failed to set modification time of {}", destination.display()))?;
+                }
+            }
+            Entry::Symlink(target) => {
+                if let Err(reason) = self.check_symlink(&destination, target) {
+                    self.reject(path, &reason);
+                    return Ok(());
+                }
+
+                std::os::unix::fs::symlink(target, &destination)
+                    .with_context(|| format!("Failed to create symlink {}", destination.display()))?;
+            }
+            Entry::HardLink(target) => {
+                let source = match self.destination(target, false) {
+                    Ok(Some(source)) if source.is_file() => source,
+                    Ok(_) => {
+                        self.reject(path, &format!("hard link to a missing file {}", target.display()));
+                        return Ok(());
+                    }
+                    Err(reason) => {
+                        self.reject(path, &format!("hard link target {reason}"));
+                        return Ok(());
+                    }
+                };
+
+                std::fs::hard_link(&source, &destination)
+                    .with_context(|| format!("Failed to create hard link {}", destination.display()))?;
+            }
+        }
+
+        self.report.extracted += 1;
+        Ok(())
+    }
+
+    /// Returns where an entry should be extracted, once its leading directory is stripped.
+    ///
+    /// Entries at the root of the archive are kept as-is, except directories, which are the ones being stripped.
+    fn destination(&self, path: &Path, directory: bool) -> Result<Option<PathBuf>, String> {
+        let mut components = vec![];
+        for component in path.components() {
+            match component {
+                Component::Normal(component) => components.push(component),
+                Component::CurDir => {}
+                Component::ParentDir => return Err("path contains a parent directory (..)".to_string()),
+                Component::RootDir | Component::Prefix(_) => return Err("path is absolute".to_string()),
+            }
+        }
+
+        match components.len() {
+            0 => Ok(None),
+            1 if directory => Ok(None),
+            1 => Ok(Some(self.root.join(components[0]))),
+            _ => Ok(Some(components[1..].iter().fold(self.root.clone(), |path, component| path.join(component)))),
+        }
+    }
+
+    /// Makes sure that no parent of an entry is a symlink, which could otherwise be used to write outside the install root.
+    fn check_parents(&self, destination: &Path) -> Result<(), String> {
+        let relative = destination.parent()
+            .and_then(|parent| parent.strip_prefix(&self.root).ok())
+            .unwrap_or_else(|| Path::new(""));
+
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push(component);
+            if current.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+                return Err(format!("path goes through the symlink {}", current.display()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure that a symlink points within the install root.
+    fn check_symlink(&self, destination: &Path, target: &Path) -> Result<(), String> {
+        if target.is_absolute() {
+            return Err(format!("symlink points to an absolute path {}", target.display()));
+        }
+
+        let mut depth = destination.parent()
+            .and_then(|parent| parent.strip_prefix(&self.root).ok())
+            .map_or(0, |relative| relative.components().count());
+
+        for component in target.components() {
+            match component {
+                Component::Normal(_) => depth += 1,
+                Component::ParentDir if depth > 0 => depth -= 1,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(format!("symlink points outside of the install root ({})", target.display()));
+                }
+                Component::CurDir => {}
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Archive formats
+//!
+//! This module contains the supported archive formats, and how to detect them from a filename or from magic bytes.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, Read};
+use std::path::Path;
+use anyhow::Context;
+
+/// The format of an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A gzipped tarball (e.g. .tar.gz)
+    TarGz,
+    /// An xz-compressed tarball (e.g. .tar.xz)
+    TarXz,
+    /// A zstd-compressed tarball (e.g. .tar.zst)
+    TarZst,
+    /// A zip archive (e.g. .zip)
+    Zip,
+}
+
+impl Format {
+    /// Returns a list of all archive formats.
+    #[must_use]
+    pub fn list() -> &'static [Self] {
+        &[Self::TarGz, Self::TarXz, Self::TarZst, Self::Zip]
+    }
+
+    /// Returns the extensions of the format, the first one being the canonical one.
+    #[must_use]
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::TarGz => &[".tar.gz", ".tgz"],
+            Self::TarXz => &[".tar.xz", ".txz"],
+            Self::TarZst => &[".tar.zst", ".tzst"],
+            Self::Zip => &[".zip"],
+        }
+    }
+
+    /// Returns the magic bytes found at the start of an archive of this format.
+    #[must_use]
+    pub fn magic(&self) -> &'static [u8] {
+        match self {
+            Self::TarGz => &[0x1F, 0x8B],
+            Self::TarXz => &[0xFD, b'7', b'z', b'X', b'Z', 0x00],
+            Self::TarZst => &[0x28, 0xB5, 0x2F, 0xFD],
+            Self::Zip => &[b'P', b'K', 0x03, 0x04],
+        }
+    }
+
+    /// Returns the format of a file from its name (or URL), if it has a known extension.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        Self::list()
+            .iter()
+            .find(|format| format.extensions().iter().any(|extension| name.ends_with(extension)))
+            .copied()
+    }
+
+    /// Returns the format of an archive from its first bytes, if they match any known format.
+    #[must_use]
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        Self::list()
+            .iter()
+            .find(|format| bytes.starts_with(format.magic()))
+            .copied()
+    }
+
+    /// Detects the format of an archive on disk.
+    ///
+    /// Magic bytes are checked first, as they cannot lie, then the extension of the given name (or of the path itself).
+    ///
+    /// # Errors
+    /// This function will return an error if the archive cannot be read, or if its format is not supported.
+    pub fn detect(path: &Path, name: Option<&str>) -> anyhow::Result<Self> {
+        let mut bytes = Vec::with_capacity(8);
+        File::open(path)
+            .and_then(|file| file.take(8).read_to_end(&mut bytes))
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let name = name.map_or_else(|| path.to_string_lossy().to_string(), str::to_string);
+
+        Self::from_magic(&bytes)
+            .or_else(|| Self::from_name(&name))
+            .ok_or_else(|| anyhow::anyhow!("Unsupported archive format: {name}"))
+    }
+
+    /// Returns whether the format is a compressed tarball.
+    #[must_use]
+    pub fn is_tar(&self) -> bool {
+        !matches!(self, Self::Zip)
+    }
+
+    /// Wraps a reader to decompress a tarball of this format.
+    ///
+    /// # Errors
+    /// This function will return an error if the format is not a tarball, or if the decoder cannot be created.
+    pub fn decoder<'a, R: BufRead + 'a>(&self, reader: R) -> anyhow::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::TarGz => Box::new(flate2::bufread::GzDecoder::new(reader)),
+            Self::TarXz => Box::new(xz2::bufread::XzDecoder::new(reader)),
+            Self::TarZst => Box::new(
+                zstd::stream::read::Decoder::with_buffer(reader)
+                    .with_context(|| "Failed to create zstd decoder")?
+            ),
+            Self::Zip => anyhow::bail!("Zip archives cannot be streamed"),
+        })
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extensions()[0].trim_start_matches('.'))
+    }
+}
@@ -13,6 +13,11 @@ mod update;
 mod cd;
 mod info;
 mod meta;
+mod versions;
+mod notes;
+mod cache;
+mod products;
+mod jbr;
 
 
 use clap::{arg, Arg, Command, value_parser};
@@ -37,6 +42,12 @@ pub fn cli() -> Command {
                 .required(false)
                 .global(true),
         )
+        .arg(
+            arg!(--offline)
+                .help("Only use cached releases, without making any request to the API")
+                .required(false)
+                .global(true),
+        )
         .arg(
             Arg::new("tools-dir")
                 .long("tools-dir")
@@ -64,6 +75,14 @@ pub fn cli() -> Command {
                 .value_parser(value_parser!(std::path::PathBuf))
                 .num_args(1),
         )
+        .arg(
+            Arg::new("api-url")
+                .long("api-url")
+                .help("The base URL of the releases API (e.g. a local mirror)")
+                .required(false)
+                .global(true)
+                .num_args(1),
+        )
         .subcommand(install::command())
         .subcommand(uninstall::command())
         .subcommand(refresh::command())
@@ -76,6 +95,11 @@ pub fn cli() -> Command {
         .subcommand(cd::command())
         .subcommand(info::command())
         .subcommand(meta::command())
+        .subcommand(versions::command())
+        .subcommand(notes::command())
+        .subcommand(cache::command())
+        .subcommand(products::command())
+        .subcommand(jbr::command())
 }
 
 pub(crate) fn dispatch(args: Option<(&str, &clap::ArgMatches)>) -> Result<()> {
@@ -84,7 +108,7 @@ pub(crate) fn dispatch(args: Option<(&str, &clap::ArgMatches)>) -> Result<()> {
             "install" => install::dispatch(sub_matches),
             "uninstall" => uninstall::dispatch(sub_matches),
             "refresh" => refresh::dispatch(sub_matches),
-            "list" => list::dispatch(),
+            "list" => list::dispatch(sub_matches),
             "link" => link::dispatch(sub_matches),
             "unlink" => unlink::dispatch(sub_matches),
             "desktop" => desktop::dispatch(),
@@ -96,6 +120,11 @@ pub(crate) fn dispatch(args: Option<(&str, &clap::ArgMatches)>) -> Result<()> {
                 Ok(())
             },
             "meta" => meta::dispatch(),
+            "versions" => versions::dispatch(sub_matches),
+            "notes" => notes::dispatch(sub_matches),
+            "cache" => cache::dispatch(sub_matches),
+            "products" => products::dispatch(sub_matches),
+            "jbr" => jbr::dispatch(sub_matches),
             _ => jb::bail!("Unknown subcommand {} provided", name),
         }
     } else {
@@ -0,0 +1,108 @@
+use std::time::SystemTime;
+use clap::Command;
+use console::style;
+use jb::archive::cache::{self, Entry};
+use jb::Result;
+use crate::emoji::*;
+
+pub(crate) fn command() -> Command {
+    Command::new("cache")
+        .about("Manage the cache of downloaded archives")
+        .long_about("Manage the cache of downloaded archives, which allows reinstalling a build without downloading it again. The cache is disabled unless JB_ARCHIVE_CACHE_SIZE is set.")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("list")
+                .about("List cached archives, most recently used first")
+        )
+        .subcommand(
+            Command::new("prune")
+                .about("Evict the least recently used archives until the cache fits in its maximum size")
+        )
+        .subcommand(
+            Command::new("clear")
+                .about("Remove all cached archives")
+        )
+}
+
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    match args.subcommand() {
+        Some(("list", _)) => list(),
+        Some(("prune", _)) => {
+            let evicted = jb::catch!(cache::prune(), "Failed to prune the archive cache");
+            summarize(&evicted, "evicted");
+            Ok(())
+        }
+        Some(("clear", _)) => {
+            let removed = jb::catch!(cache::clear(), "Failed to clear the archive cache");
+            summarize(&removed, "removed");
+            Ok(())
+        }
+        Some((name, _)) => jb::bail!("Unknown subcommand cache {} provided", name),
+        None => jb::bail!("No subcommand provided"),
+    }
+}
+
+fn list() -> Result<()> {
+    if !cache::is_enabled() {
+        jb::warn!("The archive cache is disabled, set JB_ARCHIVE_CACHE_SIZE to enable it");
+    }
+
+    let archives = jb::catch!(cache::list(), "Failed to list the archive cache");
+
+    println!(
+        "{:<15} {:<40} {:<12} {:<15}",
+        style("SHA-256").bold().underlined(),
+        style("Archive").bold().underlined(),
+        style("Size").bold().underlined(),
+        style("Last Used").bold().underlined(),
+    );
+
+    for entry in &archives {
+        let age = SystemTime::now()
+            .duration_since(entry.used)
+            .unwrap_or_default();
+
+        println!(
+            "{:<15} {:<40} {:<12} {:<15}",
+            style(&entry.sha256[..12]).dim(),
+            entry.filename,
+            humansize::format_size(entry.size, humansize::DECIMAL),
+            format!("{} ago", indicatif::HumanDuration(age)),
+        );
+    }
+
+    if archives.is_empty() {
+        println!(
+            "{}",
+            style(format!("{:<15} {:<40} {:<12} {:<15}", "Empty", "Empty", "Empty", "Empty")).italic().dim(),
+        );
+    }
+
+    let size = archives.iter().map(|entry| entry.size).sum::<u64>();
+    println!(
+        "\n{FOLDER} {} {}",
+        style(cache::directory().display()).bold(),
+        style(format!(
+            "({} out of {})",
+            humansize::format_size(size, humansize::DECIMAL),
+            humansize::format_size(cache::capacity(), humansize::DECIMAL),
+        )).dim(),
+    );
+
+    Ok(())
+}
+
+fn summarize(archives: &[Entry], action: &str) {
+    if archives.is_empty() {
+        jb::info!("{CHECK} No archives {action}");
+        return;
+    }
+
+    let size = archives.iter().map(|entry| entry.size).sum::<u64>();
+    jb::info!(
+        "{WASTEBASKET} {} {action} archive(s), freeing {}",
+        archives.len(),
+        humansize::format_size(size, humansize::DECIMAL),
+    );
+}
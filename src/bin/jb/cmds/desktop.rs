@@ -12,7 +12,7 @@ pub(crate) fn command() -> Command {
 
 pub(crate) fn dispatch() -> jb::Result<()> {
     let mut installed_tools = jb::catch!(Tool::list());
-    installed_tools.sort_by(|a, b| a.kind.cmp(&b.kind));
+    installed_tools.sort_by_key(|tool| tool.kind);
     installed_tools.dedup_by(|a, b| a.kind == b.kind);
 
     let kinds = installed_tools.iter().map(|tool| tool.kind).collect::<Vec<_>>();
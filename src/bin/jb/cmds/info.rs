@@ -15,9 +15,17 @@ pub(crate) fn dispatch() {
 
     let verbose = jb::env::Variable::Verbose.get_bool();
     let notify = jb::env::Variable::Notify.get_bool();
+    let offline = jb::env::Variable::Offline.get_bool();
     let tools_dir = jb::env::Variable::ToolsDirectory.get::<std::path::PathBuf>();
     let icons_dir = jb::env::Variable::IconsDirectory.get::<std::path::PathBuf>();
     let bin_dir = jb::env::Variable::BinariesDirectory.get::<std::path::PathBuf>();
+    let api_url = jb::env::Variable::ApiUrl.get::<String>();
+    let cache_dir = jb::env::Variable::CacheDirectory.get::<std::path::PathBuf>();
+    let cache_ttl = jb::api::cache::ttl();
+    let archive_cache = jb::archive::cache::capacity();
+    let ca_bundle = jb::env::Variable::CaBundle.get::<String>();
+    let retries = jb::env::Variable::Retries.get_u64();
+    let signature_policy = jb::verify::Policy::get();
 
     println!(
         "{} {}\n",
@@ -28,16 +36,48 @@ pub(crate) fn dispatch() {
     println!("{}", style("Environment:").bold().underlined());
     println!("- Verbose: {}", if verbose { style("true").green() } else { style("false").red() });
     println!("- Notify: {}", if notify { style("true").green() } else { style("false").red() });
+    println!("- Offline: {}", if offline { style("true").green() } else { style("false").red() });
     println!("- Tools Directory: {}", style(tools_dir.display()).dim());
     println!("- Icons Directory: {}", style(icons_dir.display()).dim());
     println!("- Binaries Directory: {}", style(bin_dir.display()).dim());
+    println!("- API URL: {}", style(api_url).dim());
+    println!("- Android Studio Feed: {}", style(jb::env::Variable::AndroidStudioFeed.get::<String>()).dim());
+    println!("- Cache Directory: {}", style(cache_dir.display()).dim());
+    println!("- Cache TTL: {}", style(indicatif::HumanDuration(cache_ttl)).dim());
+    println!("- Archive Cache: {}", style(if archive_cache == 0 {
+        "disabled".to_string()
+    } else {
+        format!("{} (up to {})", jb::archive::cache::directory().display(), humansize::format_size(archive_cache, humansize::DECIMAL))
+    }).dim());
+    println!("- CA Bundle: {}", style(if ca_bundle.is_empty() { "none" } else { &ca_bundle }).dim());
+    println!("- Timeouts: {} (connect), {} (read)",
+             style(indicatif::HumanDuration(jb::http::connect_timeout())).dim(),
+             style(indicatif::HumanDuration(jb::http::read_timeout())).dim(),
+    );
+    println!("- Retries: {}", style(retries).dim());
+    println!("- Signatures: {}", style(match signature_policy {
+        jb::verify::Policy::Off => signature_policy.to_string(),
+        _ => {
+            let keys = jb::verify::verifiers()
+                .map(|verifiers| verifiers.iter().map(|verifier| verifier.keys()).sum::<usize>())
+                .unwrap_or_default();
+            format!("{signature_policy} ({keys} trusted key{} in {})", if keys == 1 { "" } else { "s" }, jb::verify::directory().display())
+        }
+    }).dim());
+
+    println!("- Products File: {}", style(jb::tool::product::local_path().display()).dim());
+    println!("- Runtimes Directory: {}", style(jb::tool::jbr::directory().display()).dim());
 
     println!("\n{}", style("Tools:").bold().underlined());
     for kind in kinds {
-        println!("- {}: {} ({})",
+        println!("- {}: {} ({}){}",
                  style(kind).cyan(),
                  style(kind.as_str()).italic(),
                  style(Type::kind_default(*kind)).dim(),
+                 match &kind.product().download {
+                     Some(template) => format!(" {}", style(format!("from {template}")).dim()),
+                     None => String::new(),
+                 },
         );
     }
 }
\ No newline at end of file
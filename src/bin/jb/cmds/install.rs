@@ -2,18 +2,32 @@ use anyhow::Context;
 use clap::{arg, value_parser, Command};
 use jb::{Tool, Result, Batch};
 use jb::env::Variable;
-use jb::tool::{List};
+use jb::api::Target;
+use jb::tool::{List, Scope, Selector};
 use crate::emoji::*;
 
 pub(crate) fn command() -> Command {
     Command::new("install")
         .about("Install JetBrains tools")
         .arg(
-            arg!(tools: <TOOLS> "The tools to install")
-                .required(true)
-                .value_parser(value_parser!(Tool))
+            arg!(tools: [TOOLS] "The tools to install (e.g. idea-ultimate, goland@2024.1, rustrover@eap)")
+                .required_unless_present("archive")
+                .value_parser(value_parser!(Selector))
                 .num_args(1..=10),
         )
+        .arg(
+            arg!(--archive <PATH>)
+                .help("Install from a local archive instead of downloading it, the tool is read from its product-info.json")
+                .required(false)
+                .conflicts_with_all(["tools", "platform", "arch", "notes"])
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--sha256 <SHA256>)
+                .help("The expected SHA-256 checksum of the local archive")
+                .required(false)
+                .requires("archive"),
+        )
         .arg(
             arg!(-c --clean)
                 .help("Clean up old versions after installing")
@@ -24,21 +38,49 @@ pub(crate) fn command() -> Command {
                 .help("Force installation, even if the tool is already installed")
                 .required(false),
         )
+        .arg(
+            arg!(--platform <PLATFORM>)
+                .help("The platform to download the tools for (e.g. linux, mac, windows), defaults to the current one")
+                .long_help("The platform to download the tools for (e.g. linux, mac, windows), defaults to the current one\nOnly archives can be installed, so platforms whose tools only come as installers (e.g. .exe or .dmg) are refused")
+                .required(false),
+        )
+        .arg(
+            arg!(--arch <ARCH>)
+                .help("The architecture to download the tools for (e.g. x86_64, aarch64), defaults to the current one")
+                .long_help("The architecture to download the tools for (e.g. x86_64, aarch64), defaults to the current one\nTools installed for another platform or architecture are not linked")
+                .required(false),
+        )
+        .arg(
+            arg!(--notes)
+                .help("Show the release notes of the installed versions")
+                .required(false),
+        )
 }
 
 #[allow(clippy::too_many_lines)]
 pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
-    let tools: Vec<Tool> = args
-        .get_many::<Tool>("tools")
-        .expect("Could not find argument tools")
-        .map(Clone::clone)
-        .collect();
+    let tools = jb::catch!(crate::util::resolve_tools(args, "tools", Scope::Available));
+    let archive = args.get_one::<std::path::PathBuf>("archive");
+    let sha256 = args.get_one::<String>("sha256");
 
     let clean = args.get_flag("clean");
     let force = args.get_flag("force");
+    let notes = args.get_flag("notes");
     let mut error_batch = Batch::new();
 
-    let tools = crate::util::install_tools(&mut error_batch, tools, force);
+    let host = Target::host();
+    let target = jb::catch!(Target::new(
+        args.get_one::<String>("platform").unwrap_or(&host.platform),
+        args.get_one::<String>("arch").unwrap_or(&host.arch),
+    ));
+
+    let (tools, fetches) = if let Some(archive) = archive {
+        let tool = crate::util::install_archive(&mut error_batch, archive, sha256.map(String::as_str), force);
+        (tool.into_iter().collect::<Vec<_>>(), Vec::new())
+    } else {
+        let fetches = crate::util::install_tools(&mut error_batch, tools, force, &target);
+        (fetches.iter().map(|fetch| fetch.tool.clone()).collect(), fetches)
+    };
 
     if tools.is_empty() {
         jb::warn!("No tools left to install, exiting... {SKIP}");
@@ -105,6 +147,11 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
         }
     }
 
+    if notes {
+        for fetch in &fetches {
+            crate::util::print_notes(&fetch.tool, &fetch.release);
+        }
+    }
 
     if error_batch.is_empty() {
         Ok(())
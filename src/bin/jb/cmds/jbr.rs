@@ -0,0 +1,215 @@
+use std::fmt::Write;
+use clap::{arg, value_parser, Command};
+use console::style;
+use jb::{Batch, Result, Tool};
+use jb::tool::{List, Scope, Selector};
+use jb::tool::jbr::{self, Runtime};
+use crate::emoji::*;
+
+pub(crate) fn command() -> Command {
+    Command::new("jbr")
+        .about("Manage the JetBrains Runtime used by installed tools")
+        .long_about("Manage the JetBrains Runtime used by installed tools. Standalone runtimes are installed next to the tools directory (JB_JBR_DIR), and a tool is switched to one through the .jdk file of its configuration directory.")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("list")
+                .about("List installed runtimes and the tools using them")
+        )
+        .subcommand(
+            Command::new("install")
+                .about("Install standalone runtimes")
+                .arg(
+                    arg!(runtimes: <JBR> "The runtimes to install (e.g. jbr_jcef-21.0.4-b509.17 or 21.0.4b509.17)")
+                        .required(true)
+                        .value_parser(value_parser!(Runtime))
+                        .num_args(1..=10),
+                )
+                .arg(
+                    arg!(-f --force "Reinstall runtimes that are already installed")
+                        .required(false),
+                )
+        )
+        .subcommand(
+            Command::new("use")
+                .about("Run a tool on a standalone runtime, installing the runtime if needed")
+                .arg(
+                    arg!(tool: <TOOL> "The tool to switch")
+                        .required(true)
+                        .value_parser(value_parser!(Selector)),
+                )
+                .arg(
+                    arg!(runtime: <JBR> "The runtime to use")
+                        .required(true)
+                        .value_parser(value_parser!(Runtime)),
+                )
+        )
+        .subcommand(
+            Command::new("reset")
+                .about("Run a tool on its bundled runtime again")
+                .arg(
+                    arg!(tool: <TOOL> "The tool to reset")
+                        .required(true)
+                        .value_parser(value_parser!(Selector)),
+                )
+        )
+}
+
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    match args.subcommand() {
+        Some(("list", _)) => list(),
+        Some(("install", args)) => {
+            let runtimes = args.get_many::<Runtime>("runtimes")
+                .expect("Could not find argument runtimes")
+                .cloned()
+                .collect::<Vec<_>>();
+            install(runtimes, args.get_flag("force"))
+        }
+        Some(("use", args)) => {
+            let tool = jb::catch!(crate::util::resolve_tool(args, "tool", Scope::Installed));
+            let runtime = args.get_one::<Runtime>("runtime").expect("Could not find argument runtime");
+            switch(&tool, runtime)
+        }
+        Some(("reset", args)) => {
+            let tool = jb::catch!(crate::util::resolve_tool(args, "tool", Scope::Installed));
+            reset(&tool)
+        }
+        Some((name, _)) => jb::bail!("Unknown subcommand jbr {} provided", name),
+        None => jb::bail!("No subcommand provided"),
+    }
+}
+
+fn list() -> Result<()> {
+    let runtimes = jb::catch!(Runtime::list(), "Failed to list runtimes");
+
+    // Tools without a configuration directory (e.g. not based on the IntelliJ platform) cannot be switched
+    let overrides = Tool::list()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|tool| match jbr::current(&tool) {
+            Ok(Some(path)) => Some((tool, path)),
+            Ok(None) => None,
+            Err(err) => {
+                jb::debug!("Skipping {tool}: {err:#}");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{:<30} {:<15} {:<15} {}",
+        style("Runtime").bold().underlined(),
+        style("Version").bold().underlined(),
+        style("Build").bold().underlined(),
+        style("Used By").bold().underlined(),
+    );
+
+    for runtime in &runtimes {
+        let users = overrides.iter()
+            .filter(|(_, path)| path == &runtime.as_path())
+            .map(|(tool, _)| tool.as_str())
+            .collect::<Vec<_>>();
+
+        println!(
+            "{:<30} {:<15} {:<15} {}",
+            style(runtime.as_str()).cyan(),
+            runtime.version,
+            style(&runtime.build).dim(),
+            if users.is_empty() { style("-".to_string()).dim() } else { style(users.join(", ")) },
+        );
+    }
+
+    if runtimes.is_empty() {
+        println!(
+            "{}",
+            style(format!("{:<30} {:<15} {:<15} {}", "Empty", "Empty", "Empty", "Empty")).italic().dim(),
+        );
+    }
+
+    // Overrides that point outside of the installed runtimes (e.g. written by hand or to a removed runtime)
+    for (tool, path) in &overrides {
+        if !runtimes.iter().any(|runtime| &runtime.as_path() == path) {
+            jb::warn!("{tool} uses {}, which is not an installed runtime", path.display());
+        }
+    }
+
+    println!(
+        "\n{FOLDER} {} {}",
+        style(jbr::directory().display()).bold(),
+        style("(runtimes directory)").dim(),
+    );
+
+    Ok(())
+}
+
+fn install(runtimes: Vec<Runtime>, force: bool) -> Result<()> {
+    let mut error_batch = Batch::new();
+
+    jb::info!("{DOWNLOAD} Downloading runtimes...");
+
+    let ps = indicatif::ProgressStyle::with_template("{prefix:.bold.dim} [{elapsed_precise}] {wide_bar:.cyan/blue} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+        .unwrap()
+        .with_key("eta", |state: &indicatif::ProgressState, w: &mut dyn Write| write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap())
+        .progress_chars("#>-");
+
+    let mut installed = vec![];
+    for runtime in runtimes {
+        if runtime.is_installed() && !force {
+            jb::warn!("{runtime} is already installed, skipping... {SKIP}");
+            continue;
+        }
+
+        let pb = indicatif::ProgressBar::new(100);
+        pb.set_style(ps.clone());
+        pb.set_prefix(format!("[{runtime}]"));
+
+        let result = runtime.install(Some(&pb));
+        pb.finish_and_clear();
+
+        match result {
+            Ok(()) => installed.push(runtime),
+            Err(err) => {
+                jb::warn!("Failed to install {runtime}, skipping... {SKIP}");
+                error_batch.add(err.context(format!("Failed to install {runtime}")));
+            }
+        }
+    }
+
+    if !installed.is_empty() {
+        jb::info!("{CHECK} Installed runtimes:");
+        for runtime in installed {
+            println!("{PACKAGE} {runtime}");
+        }
+    }
+
+    if error_batch.is_empty() {
+        Ok(())
+    } else {
+        Err(error_batch)
+    }
+}
+
+fn switch(tool: &Tool, runtime: &Runtime) -> Result<()> {
+    let tool = jb::catch!(tool.fill(), "Failed to fill {tool}");
+
+    if !runtime.is_installed() {
+        install(vec![runtime.clone()], false)?;
+    }
+
+    jb::catch!(jbr::set(&tool, runtime), "Failed to switch {tool} to {runtime}");
+
+    jb::info!("{LINK} {tool} now runs on {runtime}, restart it for the change to take effect");
+    Ok(())
+}
+
+fn reset(tool: &Tool) -> Result<()> {
+    let tool = jb::catch!(tool.fill(), "Failed to fill {tool}");
+
+    if jb::catch!(jbr::reset(&tool), "Failed to reset the runtime of {tool}") {
+        jb::info!("{CIRCLE_ARROWS} {tool} now runs on its bundled runtime, restart it for the change to take effect");
+    } else {
+        jb::info!("{SKIP} {tool} already runs on its bundled runtime");
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use clap::{arg, value_parser, Command};
-use jb::{Tool, Result};
+use jb::Result;
 use jb::env::Variable;
-use jb::tool::Link;
+use jb::tool::{Link, Scope, Selector};
 
 pub(crate) fn command() -> Command {
     Command::new("link")
@@ -9,14 +9,12 @@ pub(crate) fn command() -> Command {
         .arg(
             arg!(tool: <TOOL> "The tool to link")
                 .required(true)
-                .value_parser(value_parser!(Tool)),
+                .value_parser(value_parser!(Selector)),
         )
 }
 
 pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
-    let tool = args
-        .get_one::<Tool>("tool")
-        .expect("Could not find argument tool");
+    let tool = jb::catch!(crate::util::resolve_tool(args, "tool", Scope::Installed));
 
     let tool = match tool.fill() {
         Ok(tool) => tool,
@@ -1,32 +1,41 @@
-use clap::Command;
+use clap::{arg, Command};
 use console::style;
 use jb::{Tool, Result};
-use jb::tool::{Link, List};
+use jb::tool::{Link, List, Metadata};
 
 pub(crate) fn command() -> Command {
     Command::new("list")
         .about("List installed JetBrains tools")
+        .arg(
+            arg!(-l --long)
+                .help("Show where each tool was installed from, as recorded when it was installed")
+                .required(false),
+        )
 }
 
-pub(crate) fn dispatch() -> Result<()> {
-    let installed_tools = match Tool::list() {
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    let long = args.get_flag("long");
+
+    let installed_tools = match Tool::list_with_metadata() {
         Ok(tools) => tools,
         Err(err) => jb::bail_with!(err, "Failed to list tools"),
     };
 
     println!(
-        "{:<1} {:<30} {:<15} {:<15} {:<15}",
+        "{:<1} {:<30} {:<15} {:<15} {:<15} {:<12} {:<10}",
         " ",
         style("Tool").bold().underlined(),
         style("Version").bold().underlined(),
         style("Build").bold().underlined(),
         style("Release Type").bold().underlined(),
+        style("Installed").bold().underlined(),
+        style("Size").bold().underlined(),
     );
 
     let checkmark = style(char::from_u32(0x2714).unwrap()).green();
     let cross = style(char::from_u32(0x2718).unwrap()).red();
 
-    for tool in &installed_tools {
+    for (tool, metadata) in &installed_tools {
         let linked = tool.is_linked();
 
         let icon = if linked {
@@ -46,24 +55,43 @@ pub(crate) fn dispatch() -> Result<()> {
         } else {
             "N/A".to_string()
         };
-        let release = if let Some(release) = &tool.release {
+        let mut release = if let Some(release) = &tool.release {
             release.to_string()
         } else {
             "N/A".to_string()
         };
 
+        // Tools built for another platform or architecture cannot be linked, so show their target
+        if let Some(metadata) = metadata.as_ref().filter(|metadata| !metadata.target.is_host()) {
+            release = format!("{release} ({})", metadata.target);
+        }
+
+        // Tools installed by older versions have no record of when they were installed or of their archive
+        let installed = metadata.as_ref()
+            .and_then(Metadata::installed_on)
+            .map_or("N/A".to_string(), |date| date.to_string());
+        let size = metadata.as_ref()
+            .and_then(|metadata| metadata.size)
+            .map_or("N/A".to_string(), |size| humansize::format_size(size, humansize::DECIMAL));
+
         if linked {
-            println!("{icon:<1} {kind:<30} {version:<15} {build:<15} {release:<15}");
+            println!("{icon:<1} {kind:<30} {version:<15} {build:<15} {release:<15} {installed:<12} {size:<10}");
         } else {
             println!(
-                "{:<1} {:<30} {:<15} {:<15} {:<15}",
+                "{:<1} {:<30} {:<15} {:<15} {:<15} {:<12} {:<10}",
                 icon,
                 style(kind).dim(),
                 style(version).dim(),
                 style(build).dim(),
                 style(release).dim(),
+                style(installed).dim(),
+                style(size).dim(),
             );
         }
+
+        if long {
+            print_metadata(metadata.as_ref());
+        }
     }
 
     if installed_tools.is_empty() {
@@ -71,12 +99,14 @@ pub(crate) fn dispatch() -> Result<()> {
             "{}",
             style(
                 format!(
-                    "{:<1} {:<30} {:<15} {:<15} {:<15}",
+                    "{:<1} {:<30} {:<15} {:<15} {:<15} {:<12} {:<10}",
                     " ",
                     "Empty",
                     "Empty",
                     "Empty",
                     "Empty",
+                    "Empty",
+                    "Empty",
                 )
             ).italic().dim(),
         );
@@ -86,3 +116,27 @@ pub(crate) fn dispatch() -> Result<()> {
 
     Ok(())
 }
+
+/// Print the metadata recorded when a tool was installed, below its row.
+fn print_metadata(metadata: Option<&Metadata>) {
+    let Some(metadata) = metadata else {
+        println!("  {}", style("No install metadata (installed by an older version of jb)").italic().dim());
+        return;
+    };
+
+    let fields = [
+        ("Source", metadata.source.clone()),
+        ("Download", Some(metadata.download.clone())),
+        ("Target", Some(metadata.target.to_string())),
+        ("SHA-256", metadata.sha256.clone()),
+        ("Released", metadata.release_date.map(|date| date.to_string())),
+        ("Channel", metadata.channel.map(|channel| channel.to_string())),
+        ("Installed By", metadata.jb_version.as_ref().map(|version| format!("jb {version}"))),
+    ];
+
+    for (name, value) in fields {
+        if let Some(value) = value {
+            println!("  {} {}", style(format!("{name}:")).dim(), value);
+        }
+    }
+}
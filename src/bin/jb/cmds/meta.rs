@@ -1,7 +1,7 @@
 use clap::Command;
 use serde::Serialize;
 use jb::Tool;
-use jb::tool::{Kind, List};
+use jb::tool::{Kind, List, Metadata, Product};
 
 pub(crate) fn command() -> Command {
     Command::new("meta")
@@ -16,12 +16,27 @@ pub(crate) fn dispatch() -> jb::Result<()> {
             env: MetaEnv {
                 verbose: jb::env::Variable::Verbose.get_bool(),
                 notify: jb::env::Variable::Notify.get_bool(),
+                offline: jb::env::Variable::Offline.get_bool(),
                 tools_dir: jb::env::Variable::ToolsDirectory.get::<std::path::PathBuf>(),
                 icons_dir: jb::env::Variable::IconsDirectory.get::<std::path::PathBuf>(),
                 bin_dir: jb::env::Variable::BinariesDirectory.get::<std::path::PathBuf>(),
+                api_url: jb::env::Variable::ApiUrl.get::<String>(),
+                android_studio_feed: jb::env::Variable::AndroidStudioFeed.get::<String>(),
+                cache_dir: jb::env::Variable::CacheDirectory.get::<std::path::PathBuf>(),
+                cache_ttl: jb::api::cache::ttl().as_secs(),
+                archive_cache_dir: jb::archive::cache::directory(),
+                archive_cache_size: jb::archive::cache::capacity(),
+                signature_policy: jb::verify::Policy::get().as_str(),
+                keys_dir: jb::verify::directory(),
+                products_file: jb::tool::product::local_path(),
+                jbr_dir: jb::tool::jbr::directory(),
             },
             kinds: Kind::list().to_vec(),
-            tools: Tool::list()?,
+            products: Kind::list().iter().map(Kind::product).collect(),
+            tools: Tool::list_with_metadata()?
+                .into_iter()
+                .map(|(tool, metadata)| MetaTool { tool, metadata })
+                .collect(),
         };
 
         println!("{}", serde_json::to_string_pretty(&meta)?);
@@ -43,14 +58,33 @@ struct Meta {
     version: &'static str,
     env: MetaEnv,
     kinds: Vec<Kind>,
-    tools: Vec<Tool>,
+    products: Vec<&'static Product>,
+    tools: Vec<MetaTool>,
+}
+
+#[derive(Serialize, Debug)]
+struct MetaTool {
+    #[serde(flatten)]
+    tool: Tool,
+    metadata: Option<Metadata>,
 }
 
 #[derive(Serialize, Debug)]
 struct MetaEnv {
     verbose: bool,
     notify: bool,
+    offline: bool,
     tools_dir: std::path::PathBuf,
     icons_dir: std::path::PathBuf,
     bin_dir: std::path::PathBuf,
+    api_url: String,
+    android_studio_feed: String,
+    cache_dir: std::path::PathBuf,
+    cache_ttl: u64,
+    archive_cache_dir: std::path::PathBuf,
+    archive_cache_size: u64,
+    signature_policy: &'static str,
+    keys_dir: std::path::PathBuf,
+    products_file: std::path::PathBuf,
+    jbr_dir: std::path::PathBuf,
 }
\ No newline at end of file
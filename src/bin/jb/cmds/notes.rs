@@ -0,0 +1,27 @@
+use clap::{arg, value_parser, Command};
+use jb::Result;
+use jb::tool::{Scope, Selector};
+
+pub(crate) fn command() -> Command {
+    Command::new("notes")
+        .about("Print the release notes of a JetBrains tool")
+        .long_about("Print the release notes of a JetBrains tool. If no version is given, the notes of the latest release are printed.")
+        .arg(
+            arg!(tool: <TOOL> "The tool to print the release notes for")
+                .required(true)
+                .value_parser(value_parser!(Selector)),
+        )
+}
+
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    let tool = jb::catch!(crate::util::resolve_tool(args, "tool", Scope::Available));
+
+    let fetch = match jb::api::fetch::release(&tool) {
+        Ok(fetch) => fetch,
+        Err(err) => jb::bail_with!(err, "Failed to fetch release for {tool}"),
+    };
+
+    crate::util::print_notes(&fetch.tool, &fetch.release);
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use clap::Command;
+use console::style;
+use jb::tool::{product, Kind};
+use jb::Result;
+use crate::emoji::*;
+
+pub(crate) fn command() -> Command {
+    Command::new("products")
+        .about("Manage the registry of installable products")
+        .long_about("Manage the registry of installable products. Products are built in, fetched from the API with `refresh`, or defined in a local registry file (JB_PRODUCTS_FILE).")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("list")
+                .about("List the products that can be installed")
+        )
+        .subcommand(
+            Command::new("refresh")
+                .about("Fetch the products from the API, to install products not yet known by the CLI")
+        )
+}
+
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    match args.subcommand() {
+        Some(("list", _)) => {
+            list();
+            Ok(())
+        }
+        Some(("refresh", _)) => refresh(),
+        Some((name, _)) => jb::bail!("Unknown subcommand products {} provided", name),
+        None => jb::bail!("No subcommand provided"),
+    }
+}
+
+fn list() {
+    println!(
+        "{:<30} {:<30} {:<8} {:<15} {:<15}",
+        style("Product").bold().underlined(),
+        style("Name").bold().underlined(),
+        style("Code").bold().underlined(),
+        style("Release Type").bold().underlined(),
+        style("Provider").bold().underlined(),
+    );
+
+    for kind in Kind::list() {
+        println!(
+            "{:<30} {:<30} {:<8} {:<15} {:<15}",
+            style(kind.as_str()).cyan(),
+            kind.to_string(),
+            style(kind.code()).dim(),
+            style(kind.channel()).dim(),
+            style(kind.provider().get().name()).dim(),
+        );
+    }
+
+    println!(
+        "\n{FOLDER} {} {}",
+        style(product::local_path().display()).bold(),
+        style("(local registry)").dim(),
+    );
+}
+
+fn refresh() -> Result<()> {
+    jb::info!("{LOOKING_GLASS} Fetching products...");
+
+    // The registry is loaded before refreshing, so that it only holds the products known so far
+    let known = Kind::list();
+    let products = jb::catch!(product::refresh(), "Failed to refresh products");
+
+    let added = products.iter()
+        .filter(|product| !known.iter().any(|kind| kind.code() == product.code || kind.as_str() == product.slug))
+        .collect::<Vec<_>>();
+
+    if added.is_empty() {
+        jb::info!("{CHECK} Fetched {} product(s), none of them are new", products.len());
+        return Ok(());
+    }
+
+    jb::info!("{PACKAGE} Fetched {} product(s), {} of them are new:", products.len(), added.len());
+    for product in added {
+        jb::info!("- {} ({})", style(&product.slug).cyan(), product.name);
+    }
+
+    Ok(())
+}
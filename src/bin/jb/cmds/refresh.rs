@@ -1,14 +1,15 @@
 use clap::{arg, Command, value_parser};
-use jb::tool::{Tool, Kind, List};
+use jb::api::{Fetch, Target};
+use jb::tool::{Constraint, Tool, Link, List, Scope, Selector};
 use crate::emoji::*;
 
 pub(crate) fn command() -> Command {
     Command::new("refresh")
         .about("Update a JetBrains tool to the latest version")
         .arg(
-            arg!(tools: <TOOLS> "The tools to update")
+            arg!(tools: <TOOLS> "The tools to update, optionally within a version constraint (e.g. idea-ultimate@~2024.1)")
                 .required(false)
-                .value_parser(value_parser!(Kind))
+                .value_parser(value_parser!(Selector))
                 .num_args(1..=10),
         )
         .arg(
@@ -16,6 +17,11 @@ pub(crate) fn command() -> Command {
                 .help("Force update, even if the tool is already up to date")
                 .required(false),
         )
+        .arg(
+            arg!(--notes)
+                .help("Show the release notes of the installed versions")
+                .required(false),
+        )
         .arg(
             arg!(-i --install)
                 .help("Install the tool if it is not already installed")
@@ -30,14 +36,16 @@ pub(crate) fn command() -> Command {
 }
 
 pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::Result<()> {
-    let kinds = args.get_many::<Kind>("tools");
+    let args_tools = args.contains_id("tools")
+        .then(|| crate::util::resolve_tools(args, "tools", Scope::Available));
     let all = args.get_flag("all");
     let force = args.get_flag("force");
     let install = args.get_flag("install");
+    let notes = args.get_flag("notes");
 
-    if kinds.is_none() && !all {
+    if args_tools.is_none() && !all {
         jb::bail!("No tools specified, nothing to update");
-    } else if all && kinds.is_some() {
+    } else if all && args_tools.is_some() {
         jb::warn!("Ignoring tools argument, --all flag is set... {SKIP}");
     }
 
@@ -46,7 +54,7 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::Result<()> {
 
     if all { // If the --all flag is set, get all installed tools
         let mut installed_tools = jb::catch!(Tool::list());
-        installed_tools.sort_by(|a, b| a.kind.cmp(&b.kind));
+        installed_tools.sort_by_key(|tool| tool.kind);
         installed_tools.dedup_by(|a, b| a.kind == b.kind);
 
         tools = installed_tools
@@ -54,9 +62,10 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::Result<()> {
             .map(|tool| Tool::from_kind(tool.kind))
             .collect();
     } else { // Otherwise, get the tools from the arguments
-        tools = kinds.unwrap().map(Clone::clone)
-            .filter(|kind| {
-                let tools = Tool::list_kind(*kind);
+        tools = jb::catch!(args_tools.unwrap()).into_iter()
+            .filter(|tool| {
+                let kind = tool.kind;
+                let tools = Tool::list_kind(kind);
                 if tools.is_err() || tools.unwrap().is_empty() {
                     if install {
                         jb::warn!("No tools found for {kind}, but installing anyway...");
@@ -67,30 +76,42 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::Result<()> {
                 }
                 true
             })
-            .map(Tool::from_kind)
+            // Only the kind, release type and constraint are kept, as the latest matching release is installed,
+            // so a plain version or build number becomes part of the constraint
+            .map(|tool| Tool::new(tool.kind, None, None, tool.release).with_constraint(Constraint::for_tool(&tool)))
             .collect();
 
         tools.sort(); tools.dedup();
     }
 
-    let mut old_tools: Vec<Tool> = tools
-        .iter()
-        .filter_map(|tool| {
-            // Either find the linked tool for this kind, or the latest version
-            let linked = tool.kind.linked().unwrap();
-            if let Some(linked) = linked {
-                Some(linked)
-            } else {
-                tool.kind.latest().unwrap()
-            }
-        })
-        .collect();
-
     if tools.is_empty() {
         jb::bail!("No tools found, nothing to update");
     }
 
-    tools = crate::util::install_tools(&mut error_batch, tools, force);
+    let mut old_tools: Vec<Tool> = Vec::new();
+    let mut targets: Vec<(Target, Vec<Tool>)> = Vec::new();
+    for tool in tools {
+        // Either find the linked tool matching this tool, or the latest version
+        let matching = tool.list_matching().unwrap();
+        let linked = matching.iter().find(|tool| tool.is_linked()).cloned();
+        let old_tool = linked.or_else(|| matching.into_iter().max());
+
+        // Tools are updated for the target they were installed for
+        let target = old_tool.as_ref()
+            .and_then(Tool::metadata)
+            .map_or_else(Target::host, |metadata| metadata.target);
+        match targets.iter_mut().find(|(other, _)| *other == target) {
+            Some((_, tools)) => tools.push(tool),
+            None => targets.push((target, vec![tool])),
+        }
+
+        old_tools.extend(old_tool);
+    }
+
+    let fetches: Vec<Fetch> = targets.into_iter()
+        .flat_map(|(target, tools)| crate::util::install_tools(&mut error_batch, tools, force, &target))
+        .collect();
+    tools = fetches.iter().map(|fetch| fetch.tool.clone()).collect();
 
     if tools.is_empty() {
         jb::warn!("No tools left to update, exiting... {SKIP}");
@@ -134,6 +155,12 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::Result<()> {
         }
     }
 
+    if notes {
+        for fetch in &fetches {
+            crate::util::print_notes(&fetch.tool, &fetch.release);
+        }
+    }
+
     if error_batch.is_empty() {
         Ok(())
     } else {
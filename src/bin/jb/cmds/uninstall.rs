@@ -1,7 +1,7 @@
 use clap::{arg, value_parser, Command};
 use jb::{Tool, Result, Batch};
 use jb::env::Variable;
-use jb::tool::{Link, List};
+use jb::tool::{Link, List, Scope, Selector};
 use crate::emoji::*;
 
 pub(crate) fn command() -> Command {
@@ -10,17 +10,13 @@ pub(crate) fn command() -> Command {
         .arg(
             arg!(tools: <TOOLS> "The tools to uninstall")
                 .required(true)
-                .value_parser(value_parser!(Tool))
+                .value_parser(value_parser!(Selector))
                 .num_args(1..=10),
         )
 }
 
 pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
-    let args_tools: Vec<_> = args
-        .get_many::<Tool>("tools")
-        .expect("Could not find argument tool")
-        .map(Clone::clone)
-        .collect();
+    let args_tools = jb::catch!(crate::util::resolve_tools(args, "tools", Scope::Installed));
 
     let mut error_batch = Batch::new();
 
@@ -2,7 +2,6 @@ use anyhow::Context;
 use clap::{Command,arg};
 use crate::update::Release;
 use crate::emoji::CHECK;
-use termimad::crossterm::style::Color;
 use jb::env::Variable;
 
 pub(crate) fn command() -> Command {
@@ -28,12 +27,7 @@ pub(crate) fn dispatch(args: &clap::ArgMatches) -> jb::error::Result<()> {
         Ok(changelog) => {
             jb::info!("{CHECK} Updated to the latest version");
             if !changelog.is_empty() {
-                let mut skin = termimad::MadSkin::default();
-                skin.bold.set_fg(Color::AnsiValue(208));
-                skin.italic.set_fg(Color::AnsiValue(208));
-                skin.set_headers_fg(Color::Cyan);
-
-                skin.print_text(&changelog);
+                crate::util::skin().print_text(&changelog);
             }
 
             if Variable::Notify.get_bool() {
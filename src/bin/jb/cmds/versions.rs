@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use clap::{arg, value_parser, Command};
+use console::style;
+use jb::Result;
+use jb::api::Release;
+use jb::tool::{Kind, Type};
+use jb::tool::version::Major;
+
+pub(crate) fn command() -> Command {
+    Command::new("versions")
+        .about("List available releases of JetBrains tools")
+        .visible_alias("search")
+        .arg(
+            arg!(tools: <TOOLS> "The tools to list releases for")
+                .required(true)
+                .value_parser(value_parser!(Kind))
+                .num_args(1..=10),
+        )
+        .arg(
+            arg!(-t --type <TYPE>)
+                .help("Only list releases of the given types (defaults to the default type of each tool)")
+                .value_parser(value_parser!(Type))
+                .num_args(1..=3)
+                .required(false),
+        )
+        .arg(
+            arg!(-m --major <MAJOR>)
+                .help("Only list releases of the given major version (e.g. 2024.1)")
+                .value_parser(value_parser!(Major))
+                .required(false),
+        )
+        .arg(
+            arg!(-l --limit <LIMIT>)
+                .help("The maximum number of releases to list for each tool")
+                .value_parser(value_parser!(usize))
+                .required(false),
+        )
+        .arg(
+            arg!(--json)
+                .help("Print the releases in JSON format")
+                .required(false),
+        )
+}
+
+pub(crate) fn dispatch(args: &clap::ArgMatches) -> Result<()> {
+    let mut kinds: Vec<Kind> = args
+        .get_many::<Kind>("tools")
+        .expect("Could not find argument tools")
+        .copied()
+        .collect();
+    kinds.sort(); kinds.dedup();
+
+    let types: Vec<Type> = args
+        .get_many::<Type>("type")
+        .map(|types| types.copied().collect())
+        .unwrap_or_default();
+    let major = args.get_one::<Major>("major").cloned();
+    let limit = args.get_one::<usize>("limit").copied().unwrap_or(usize::MAX);
+    let json = args.get_flag("json");
+
+    let mut error_batch = jb::Batch::new();
+
+    let inputs = kinds.into_iter()
+        .map(|kind| (kind, types.clone(), major.clone()))
+        .collect::<Vec<_>>();
+
+    let mut results = crate::concurrent_step!(error_batch, inputs, |(kind, types, major): (Kind, Vec<Type>, Option<Major>)| {
+        let releases = jb::api::fetch::releases(kind, &types)?
+            .into_iter()
+            .filter(|release| major.as_ref().map_or(true, |major| &release.major() == major))
+            .collect::<Vec<_>>();
+
+        Ok((kind, releases))
+    });
+
+    for (_, releases) in &mut results {
+        releases.truncate(limit);
+    }
+
+    if json {
+        let releases = results.iter()
+            .map(|(kind, releases)| (kind.as_str(), releases))
+            .collect::<BTreeMap<_, _>>();
+
+        jb::catch_with!(error_batch, serde_json::to_string_pretty(&releases).map(|json| println!("{json}")));
+    } else {
+        print_table(&results);
+    }
+
+    if error_batch.is_empty() {
+        Ok(())
+    } else {
+        Err(error_batch)
+    }
+}
+
+fn print_table(results: &[(Kind, Vec<Release>)]) {
+    println!(
+        "{:<30} {:<15} {:<15} {:<15} {:<10} {:<10}",
+        style("Tool").bold().underlined(),
+        style("Version").bold().underlined(),
+        style("Build").bold().underlined(),
+        style("Release Type").bold().underlined(),
+        style("Size").bold().underlined(),
+        style("Date").bold().underlined(),
+    );
+
+    for (kind, releases) in results {
+        for release in releases {
+            let size = release.download()
+                .ok()
+                .and_then(|download| download.size())
+                .map_or("N/A".to_string(), |size| humansize::format_size(size, humansize::DECIMAL));
+
+            let date = release.date.map_or("N/A".to_string(), |date| date.to_string());
+
+            println!(
+                "{:<30} {:<15} {:<15} {:<15} {:<10} {:<10}",
+                kind.to_string(),
+                release.version.to_string(),
+                release.build.to_string(),
+                release.release.to_string(),
+                size,
+                style(date).dim(),
+            );
+        }
+
+        if releases.is_empty() {
+            println!(
+                "{}",
+                style(format!("{:<30} {:<15}", kind.to_string(), "No releases found")).italic().dim(),
+            );
+        }
+    }
+}
@@ -14,4 +14,5 @@ pub static FOLDER: Emoji = Emoji("\u{1F4C2}", "");
 pub static CIRCLE_ARROWS: Emoji = Emoji("\u{1F504}", "");
 pub static PACKAGE: Emoji = Emoji("\u{1F4E6}", "");
 pub static FIRECRACKER: Emoji = Emoji("\u{1F9E8}", "");
-pub static DESKTOP: Emoji = Emoji("\u{1F5A5}\u{FE0F} ", "");
\ No newline at end of file
+pub static DESKTOP: Emoji = Emoji("\u{1F5A5}\u{FE0F} ", "");
+pub static NEWSPAPER: Emoji = Emoji("\u{1F4F0}", "");
\ No newline at end of file
@@ -11,6 +11,10 @@ fn main() {
     let matches = cli.get_matches();
 
     update_env(&matches);
+    if let Err(err) = jb::env::config() {
+        jb::warn!("Ignoring the configuration file: {err:#}");
+    }
+
     match cmds::dispatch(matches.subcommand()) {
         Ok(()) => {}
         Err(e) => {
@@ -26,6 +30,11 @@ fn update_env(matches: &clap::ArgMatches) {
         Variable::Notify.set("true");
     }
 
+    let offline = matches.get_flag("offline");
+    if offline {
+        Variable::Offline.set("true");
+    }
+
     let tools_dir = matches.get_one::<std::path::PathBuf>("tools-dir");
     if let Some(tools_dir) = tools_dir {
         Variable::ToolsDirectory.set(tools_dir.to_str().unwrap().to_string());
@@ -41,6 +50,11 @@ fn update_env(matches: &clap::ArgMatches) {
         Variable::BinariesDirectory.set(bin_dir.to_str().unwrap().to_string());
     }
 
+    let api_url = matches.get_one::<String>("api-url");
+    if let Some(api_url) = api_url {
+        Variable::ApiUrl.set(api_url);
+    }
+
     let verbose = matches.get_flag("verbose");
     if verbose {
         Variable::Verbose.set("true");
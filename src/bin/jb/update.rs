@@ -19,17 +19,16 @@ impl Release {
         let url = repo.replace("github.com", "api.github.com/repos")
             + "/releases/latest";
 
-        let client = reqwest::blocking::Client::new();
-
-        let response = client.get(&url)
-            .header("User-Agent", "JetBrains CLI")
-            .send()
-            .with_context(|| format!("Failed to get latest release from {url}"))?;
-
-        let release = response.json::<Release>()
-            .with_context(|| format!("Failed to parse latest release from {url}"))?;
-
-        Ok(release)
+        let client = jb::http::blocking()?;
+
+        jb::http::retry(|| {
+            client.get(&url)
+                .send()
+                .and_then(reqwest::blocking::Response::error_for_status)
+                .with_context(|| format!("Failed to get latest release from {url}"))?
+                .json::<Release>()
+                .with_context(|| format!("Failed to parse latest release from {url}"))
+        })
     }
 
     /// Try updating the CLI, if there is a new release.
@@ -65,7 +64,7 @@ impl Release {
                 latest.tag_name,
             );
 
-            jb::util::download_extract(&url, &tempdir.path().to_path_buf(), None, None)?;
+            jb::util::download_extract(&url, &tempdir.path().to_path_buf(), None, None, None)?;
 
             // Delete current binary
             std::fs::remove_file(location)
@@ -1,36 +1,64 @@
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
 use anyhow::Context;
-use jb::tool::{Link, Probe};
+use console::style;
+use termimad::crossterm::style::Color;
+use jb::api::{Fetch, Target};
+use jb::env::Variable;
+use jb::tool::{Install, Link, Metadata, ProductInfo, Scope, Selector};
 use crate::emoji::*;
 
-pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, force: bool) -> Vec<jb::Tool> {
-    jb::info!("{LOOKING_GLASS} Resolving tool releases...");
+/// Resolve the tools selected by an argument (see [`Selector::resolve`]).
+pub(crate) fn resolve_tools(args: &clap::ArgMatches, id: &str, scope: Scope) -> anyhow::Result<Vec<jb::Tool>> {
+    args.get_many::<Selector>(id)
+        .into_iter()
+        .flatten()
+        .map(|selector| selector.resolve(scope).with_context(|| format!("Failed to resolve {selector}")))
+        .collect()
+}
 
-    // First step, find releases for all tools. If any fails, ignore them (while warning)
-    let mut tools = crate::concurrent_step!(error_batch, tools, |mut tool: jb::Tool| {
-        jb::make!("{}", tool.as_str());
+/// Resolve the tool selected by a required argument (see [`Selector::resolve`]).
+pub(crate) fn resolve_tool(args: &clap::ArgMatches, id: &str, scope: Scope) -> anyhow::Result<jb::Tool> {
+    let selector = args.get_one::<Selector>(id)
+        .unwrap_or_else(|| panic!("Could not find argument {id}"));
+
+    selector.resolve(scope)
+        .with_context(|| format!("Failed to resolve {selector}"))
+}
+
+pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, force: bool, target: &Target) -> Vec<Fetch> {
+    jb::info!("{LOOKING_GLASS} Resolving tool releases...");
 
-        let release = match tool.sync() {
-            Ok(release) => release,
+    // First step, find releases for all tools in as few requests as possible. If any fails, ignore them (while warning)
+    let fetches = jb::api::fetch::batch_for(&tools, target);
+    let mut tools: Vec<Fetch> = tools.iter()
+        .zip(fetches)
+        .filter_map(|(tool, fetch)| match fetch {
+            Ok(fetch) => {
+                jb::debug!("Found release: {}", fetch.tool);
+                Some(fetch)
+            }
             Err(err) => {
                 jb::warn!("Failed to fetch release for {tool}, skipping... {SKIP}");
-                return Err(err);
+                error_batch.add(err);
+                None
             }
-        };
-
-        jb::debug!("Found release: {tool}");
-        Ok((tool, release))
-    });
+        })
+        .collect();
 
     if tools.is_empty() {
         return Vec::new();
     }
 
-    tools.sort_by(|a, b| a.0.cmp(&b.0));
-    tools.dedup_by(|a, b| a.0 == b.0);
+    tools.sort_by(|a, b| a.tool.cmp(&b.tool));
+    tools.dedup_by(|a, b| a.tool == b.tool);
 
-    let tools: Vec<(jb::Tool, jb::api::deserial::Download)> = tools.iter().filter(|(tool, _)| {
+    let tools: Vec<Fetch> = tools.into_iter().filter(|Fetch { tool, .. }| {
         if tool.as_path().exists() {
+            if let Err(err) = check_target(tool, target) {
+                error_batch.add(err);
+                return false;
+            }
             if force {
                 jb::warn!("{tool} is already installed, but force is enabled, continuing...");
             } else {
@@ -39,12 +67,35 @@ pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, f
             }
         }
         true
-    }).map(|(tool, release)| (tool.clone(), release.clone())).collect();
+    }).collect();
 
     if tools.is_empty() {
         return Vec::new();
     }
 
+    // Pre-flight, make sure that the archives and their extraction fit on disk before starting anything
+    // Cached archives are not downloaded again, and their actual size is known
+    let (downloads, archives) = tools.iter()
+        .map(|Fetch { download, .. }| {
+            match jb::archive::cache::find(download.checksum.as_deref(), &download.link) {
+                Some(entry) => (0, entry.size),
+                None => (download.size, download.size),
+            }
+        })
+        .fold((0u64, 0u64), |(downloads, archives), (download, archive)| (downloads + download, archives + archive));
+    let requirements = [
+        (jb::util::staging_directory(), downloads),
+        (Variable::ToolsDirectory.get::<PathBuf>(), archives.saturating_mul(Variable::ExpansionFactor.get_u64())),
+    ];
+    if let Err(err) = jb::util::check_space(&requirements) {
+        if force {
+            jb::warn!("{err}, but force is enabled, continuing...");
+        } else {
+            error_batch.add(err.context("Not enough disk space, use --force to install anyway"));
+            return Vec::new();
+        }
+    }
+
     jb::info!("{DOWNLOAD} Downloading tools...");
 
     let m = indicatif::MultiProgress::new();
@@ -53,36 +104,28 @@ pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, f
         .with_key("eta", |state: &indicatif::ProgressState, w: &mut dyn Write| write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap())
         .progress_chars("#>-");
 
-    let tools = crate::concurrent_step!(error_batch, tools, |(tool, release): (jb::Tool, jb::api::deserial::Download)| {
+    let tools = tools.into_iter()
+        .map(|fetch| (fetch, target.clone()))
+        .collect::<Vec<_>>();
+
+    let tools = crate::concurrent_step!(error_batch, tools, |(fetch, target): (Fetch, Target)| {
+        let Fetch { tool, release, download } = &fetch;
         jb::make!("{}", tool.as_str());
 
         pb.set_prefix(format!("[{}]", tool.as_str()));
 
-        let install_dir = tool.as_path();
-
-        // Make sure to pre-delete the directory if it exists
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir)
-                .with_context(|| format!("Failed to clean up {}", install_dir.display()))?;
-        }
+        let metadata = Metadata::from_release(release, download, target);
 
-        let result = jb::util::download_extract(&release.link, &install_dir, Some(&release.checksum_link), Some(&pb))
+        let result = tool.install(download, &metadata, Some(&pb))
             .with_context(|| format!("Failed to download {}", tool.as_str()));
 
         pb.finish();
         if let Err(e) = result {
             jb::warn!("Failed to download {}, skipping... {SKIP}", tool.as_str());
-
-            // Make sure to delete the directory if it was created
-            if install_dir.exists() {
-                std::fs::remove_dir_all(&install_dir)
-                    .with_context(|| format!("Failed to clean up {}", install_dir.display()))?;
-            }
-
             return Err(e);
         }
 
-        Ok(tool)
+        Ok(fetch)
     }, {
         let pb = m.add(indicatif::ProgressBar::new(100));
         pb.set_style(ps.clone());
@@ -94,11 +137,109 @@ pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, f
         return tools;
     }
 
+    // Tools built for another platform or architecture cannot be linked on this host
+    if !target.is_host() {
+        jb::info!("{LINK} Tools were installed for {target}, skipping linking... {SKIP}");
+        return tools;
+    }
+
     // Third step, link all tools. If any fails, ignore them (while warning)
+    link_tools(error_batch, tools.iter().map(|fetch| fetch.tool.clone()).collect());
+
+    tools
+}
+
+/// Install a tool from a local archive, working out which tool it is from its product information.
+///
+/// The archive goes through the same staging and verification as downloaded ones, then the tool is linked.
+pub(crate) fn install_archive(error_batch: &mut jb::Batch, archive: &Path, sha256: Option<&str>, force: bool) -> Option<jb::Tool> {
+    jb::info!("{LOOKING_GLASS} Reading {}...", archive.display());
+
+    let read = || {
+        let info = ProductInfo::from_archive(archive)?;
+        let tool = info.tool()?;
+        Ok::<_, anyhow::Error>((info, tool))
+    };
+
+    let (info, tool) = match read() {
+        Ok(result) => result,
+        Err(err) => {
+            error_batch.add(err.context(format!("Failed to read {}", archive.display())));
+            return None;
+        }
+    };
+    jb::debug!("Found tool in archive: {tool}");
+
+    let target = info.target().unwrap_or_else(Target::host);
+    if tool.as_path().exists() {
+        if let Err(err) = check_target(&tool, &target) {
+            error_batch.add(err);
+            return None;
+        }
+        if force {
+            jb::warn!("{tool} is already installed, but force is enabled, continuing...");
+        } else {
+            jb::warn!("{tool} is already installed, skipping... {SKIP}");
+            return None;
+        }
+    }
+
+    jb::info!("{PACKAGE} Extracting {}...", archive.display());
+
+    let source = archive.canonicalize().unwrap_or_else(|_| archive.to_path_buf());
+    let metadata = Metadata::new("local", target)
+        .with_source(source.display().to_string())
+        .with_channel(tool.release);
+
+    let result = tool.install_with(&metadata, |staging| {
+        let size = std::fs::metadata(archive)
+            .with_context(|| format!("Failed to read {}", archive.display()))?
+            .len();
+        let hash = jb::util::extract(archive, staging)?;
+
+        if let Some(sha256) = sha256 {
+            if !hash.eq_ignore_ascii_case(sha256.trim()) {
+                anyhow::bail!("Checksum mismatch: expected {}, got {hash}", sha256.trim());
+            }
+        }
+
+        Ok(jb::util::Archive { sha256: hash, size })
+    });
+
+    if let Err(err) = result {
+        error_batch.add(err.context(format!("Failed to install {}", tool.as_str())));
+        return None;
+    }
+
+    if metadata.target.is_host() {
+        link_tools(error_batch, vec![tool.clone()]);
+    } else {
+        jb::info!("{LINK} Tool was built for {}, skipping linking... {SKIP}", metadata.target);
+    }
+
+    Some(tool)
+}
+
+/// Make sure that an installed tool was installed for a target, as tools share their directory across targets.
+///
+/// Tools without metadata are assumed to be installed for the current host.
+fn check_target(tool: &jb::Tool, target: &Target) -> anyhow::Result<()> {
+    let installed = tool.metadata()
+        .map_or_else(Target::host, |metadata| metadata.target);
+
+    if installed != *target {
+        anyhow::bail!("{tool} is already installed for {installed}, uninstall it before installing it for {target}");
+    }
+
+    Ok(())
+}
+
+/// Link tools, keeping only the latest version of each kind.
+pub(crate) fn link_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>) {
     jb::info!("{LINK} Linking tools...");
 
     //* Remove all duplicate tool kinds, keeping the latest version (we can only have one version linked of each tool)
-    let mut filtered_tools = tools.clone();
+    let mut filtered_tools = tools;
     filtered_tools.sort();
     filtered_tools.dedup_by(|a, b| a.kind == b.kind);
 
@@ -115,8 +256,33 @@ pub(crate) fn install_tools(error_batch: &mut jb::Batch, tools: Vec<jb::Tool>, f
 
         Ok(())
     });
+}
 
-    tools
+/// Print the release notes of a tool, if any.
+pub(crate) fn print_notes(tool: &jb::Tool, release: &jb::api::Release) {
+    println!(
+        "\n{NEWSPAPER} {} {}",
+        style(format!("What's new in {tool}")).bold().underlined(),
+        style(release.date.map_or(String::new(), |date| format!("(released on {date})"))).dim(),
+    );
+
+    match release.notes() {
+        Some(notes) => skin().print_text(&notes),
+        None => println!("{}", style("No release notes available").italic().dim()),
+    }
+
+    if let Some(link) = &release.notes_link {
+        println!("{} {}", style("Full release notes:").dim(), style(link).cyan());
+    }
+}
+
+/// Get the skin used to print markdown to the terminal.
+pub(crate) fn skin() -> termimad::MadSkin {
+    let mut skin = termimad::MadSkin::default();
+    skin.bold.set_fg(Color::AnsiValue(208));
+    skin.italic.set_fg(Color::AnsiValue(208));
+    skin.set_headers_fg(Color::Cyan);
+    skin
 }
 
 #[macro_export]
@@ -1,9 +1,18 @@
 //! Module for handling defaults and environment variables.
+//!
+//! Variables are taken from the environment first, then from the configuration file (see [`Variable::ConfigFile`]),
+//! and finally from their default values.
 
+use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
 use std::io::IsTerminal;
+use anyhow::Context;
 use nix::unistd::Uid;
+use once_cell::sync::Lazy;
+
+/// The configuration file, loaded once on first use.
+static CONFIG: Lazy<anyhow::Result<HashMap<String, String>>> = Lazy::new(load_config);
 
 #[derive(Debug, Clone, Copy)]
 pub enum Variable {
@@ -42,6 +51,104 @@ pub enum Variable {
     ///
     /// `JB_SYSTEMD_DIR`
     SystemdDirectory,
+    /// The directory where standalone `JetBrains` Runtime builds are installed. (e.g. /usr/local/share/JetBrains/jbr)
+    /// The default value is the `jbr` folder next to the tools directory.
+    ///
+    /// `JB_JBR_DIR`
+    JbrDirectory,
+
+    /// The base URL of the releases API. (e.g. <https://data.services.jetbrains.com>)
+    /// Any server returning the same JSON as `JetBrains`' API can be used, such as a local mirror.
+    /// Relative download and checksum links in its responses are resolved against this URL.
+    ///
+    /// `JB_API_URL`
+    ApiUrl,
+    /// The URL of Android Studio's release feed, in the JSON format published by `JetBrains`.
+    /// Any server returning the same JSON can be used, such as a local mirror.
+    ///
+    /// `JB_ANDROID_STUDIO_FEED`
+    AndroidStudioFeed,
+    /// The base URL of `JetBrains` Runtime archives. (e.g. <https://cache-redirector.jetbrains.com/intellij-jbr>)
+    /// Any server with the same archive names can be used, such as a local mirror.
+    ///
+    /// `JB_JBR_URL`
+    JbrUrl,
+
+    /// The directory where cached data is stored. (e.g. /var/cache/jb)
+    /// The default value is $XDG_CACHE_HOME/jb, $HOME/.cache/jb or /var/cache/jb if running as root.
+    ///
+    /// `JB_CACHE_DIR`
+    CacheDirectory,
+    /// The time in seconds during which cached releases are considered fresh.
+    /// The default value is 3600 (1 hour), and 0 disables the cache.
+    ///
+    /// `JB_CACHE_TTL`
+    CacheTtl,
+    /// Whether to only use cached releases, without making any request to the API.
+    ///
+    /// `JB_OFFLINE`
+    Offline,
+    /// The directory where downloaded archives are cached, by checksum. (e.g. a shared NFS mount)
+    /// The default value is the `archives` folder of the cache directory.
+    ///
+    /// `JB_ARCHIVE_CACHE_DIR`
+    ArchiveCacheDirectory,
+    /// The maximum size in megabytes of the archive cache, after which the least recently used archives are evicted.
+    /// The default value is 0, which disables the archive cache.
+    ///
+    /// `JB_ARCHIVE_CACHE_SIZE`
+    ArchiveCacheSize,
+    /// The ratio between the size of an extracted tool and its archive, used to check for free space before installing.
+    /// The default value is 3, and 0 disables the check for the tools directory.
+    ///
+    /// `JB_EXPANSION_FACTOR`
+    ExpansionFactor,
+
+    /// The path to an additional PEM bundle of trusted certificate authorities. (e.g. for a TLS-inspecting proxy)
+    /// Proxies themselves are configured with the usual `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables.
+    ///
+    /// `JB_CA_BUNDLE`
+    CaBundle,
+    /// The timeout in seconds for connecting to a server.
+    /// The default value is 30.
+    ///
+    /// `JB_CONNECT_TIMEOUT`
+    ConnectTimeout,
+    /// The timeout in seconds for reading a response, or a chunk of a download.
+    /// The default value is 60.
+    ///
+    /// `JB_READ_TIMEOUT`
+    ReadTimeout,
+    /// The number of times a request is retried after a transient error, with an exponential backoff.
+    /// The default value is 3.
+    ///
+    /// `JB_RETRIES`
+    Retries,
+
+    /// Whether detached signatures of downloaded archives are checked, and what happens when they cannot be verified.
+    /// Either `off`, `warn` or `require`. The default value is off.
+    ///
+    /// `JB_SIGNATURE_POLICY`
+    SignaturePolicy,
+    /// The directory of trusted public keys used to verify signatures. (e.g. /etc/jb/keys)
+    /// Files ending with `.pub` are minisign keys, and files ending with `.asc` or `.gpg` are `OpenPGP` keys.
+    /// The default value is $XDG_CONFIG_HOME/jb/keys, $HOME/.config/jb/keys or /etc/jb/keys if running as root.
+    ///
+    /// `JB_KEYS_DIR`
+    KeysDirectory,
+
+    /// The path to a local product registry, which adds products to the built-in ones or overrides them.
+    /// The default value is $XDG_CONFIG_HOME/jb/products.json, $HOME/.config/jb/products.json or /etc/jb/products.json if running as root.
+    ///
+    /// `JB_PRODUCTS_FILE`
+    ProductsFile,
+    /// The path to the configuration file, a JSON object whose keys are the names of the variables
+    /// without their `JB_` prefix and in lowercase (e.g. `{"api_url": "https://mirror.example.com"}`).
+    /// Environment variables and command line flags take precedence over the configuration file.
+    /// The default value is $XDG_CONFIG_HOME/jb/config.json, $HOME/.config/jb/config.json or /etc/jb/config.json if running as root.
+    ///
+    /// `JB_CONFIG_FILE`
+    ConfigFile,
 }
 
 impl Variable {
@@ -60,6 +167,22 @@ impl Variable {
         matches!(self.get::<String>().as_str(), "true" | "1")
     }
 
+    /// Get the value of the variable as an unsigned number.
+    ///
+    /// Invalid values fall back to the default value, with a warning.
+    ///
+    /// # Panics
+    /// If the default value is not a valid number.
+    #[must_use]
+    pub fn get_u64(&self) -> u64 {
+        let value = self.get::<String>();
+
+        value.trim().parse::<u64>().unwrap_or_else(|_| {
+            crate::warn!("Invalid value {value} for {}, using the default value", self.env());
+            self.default::<String>().parse().expect("Default value is not a number")
+        })
+    }
+
     /// Set the value of the variable.
     pub fn set<T>(&self, value: T)
     where
@@ -85,16 +208,31 @@ impl Variable {
         env::set_var(var.env(), value.into());
     }
 
-    /// Get the value of a variable, or a default value if it is not set.
+    /// Get the value of a variable, or a default value if it is set neither in the environment nor in the configuration file.
     #[must_use]
     pub fn get_or<T>(&self, default: T) -> T
     where
         T: From<String> + Send + Sync,
     {
-        match env::var(self.env()) {
-            Ok(value) => value.into(),
-            Err(_) => default,
+        if let Ok(value) = env::var(self.env()) {
+            return value.into();
+        }
+
+        // The configuration file cannot set its own path
+        if matches!(self, Variable::ConfigFile) {
+            return default;
         }
+
+        match CONFIG.as_ref().ok().and_then(|config| config.get(&self.key())) {
+            Some(value) => value.clone().into(),
+            None => default,
+        }
+    }
+
+    /// Get the key of the variable in the configuration file (e.g. `api_url` for `JB_API_URL`).
+    #[must_use]
+    pub fn key(&self) -> String {
+        self.env().trim_start_matches("JB_").to_lowercase()
     }
 
     /// Get the default value for a variable.
@@ -111,9 +249,11 @@ impl Variable {
             Variable::Verbose => "false".to_string().into(),
             Variable::Notify => {
                 // True if terminal is not interactive else false
-                std::io::stdout().is_terminal()
-                    .then(|| "false".to_string().into())
-                    .unwrap_or("true".to_string().into())
+                if std::io::stdout().is_terminal() {
+                    "false".to_string().into()
+                } else {
+                    "true".to_string().into()
+                }
             }
             Variable::ToolsDirectory => {
                 if Self::is_root() {
@@ -175,6 +315,101 @@ impl Variable {
                     .to_string()
                     .into()
             },
+            Variable::JbrDirectory => {
+                let tools_dir = Variable::ToolsDirectory.get::<PathBuf>();
+
+                tools_dir.parent()
+                    .unwrap_or(&tools_dir)
+                    .join("jbr")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
+            Variable::ApiUrl => "https://data.services.jetbrains.com".to_string().into(),
+            Variable::AndroidStudioFeed => "https://jb.gg/android-studio-releases-list.json".to_string().into(),
+            Variable::JbrUrl => "https://cache-redirector.jetbrains.com/intellij-jbr".to_string().into(),
+            Variable::CacheDirectory => {
+                if Self::is_root() {
+                    return "/var/cache/jb".to_string().into();
+                }
+
+                env::var("XDG_CACHE_HOME")
+                    .map_or_else(
+                        |_| PathBuf::from(env::var("HOME").expect("HOME environment variable not set")).join(".cache"),
+                        PathBuf::from,
+                    )
+                    .join("jb")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
+            Variable::CacheTtl => "3600".to_string().into(),
+            Variable::Offline => "false".to_string().into(),
+            Variable::ArchiveCacheDirectory => {
+                PathBuf::from(Variable::CacheDirectory.get::<String>())
+                    .join("archives")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
+            Variable::ArchiveCacheSize => "0".to_string().into(),
+            Variable::ExpansionFactor => "3".to_string().into(),
+            Variable::CaBundle => String::new().into(),
+            Variable::ConnectTimeout => "30".to_string().into(),
+            Variable::ReadTimeout => "60".to_string().into(),
+            Variable::Retries => "3".to_string().into(),
+            Variable::SignaturePolicy => "off".to_string().into(),
+            Variable::KeysDirectory => {
+                if Self::is_root() {
+                    return "/etc/jb/keys".to_string().into();
+                }
+
+                env::var("XDG_CONFIG_HOME")
+                    .map_or_else(
+                        |_| PathBuf::from(env::var("HOME").expect("HOME environment variable not set")).join(".config"),
+                        PathBuf::from,
+                    )
+                    .join("jb/keys")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
+            Variable::ProductsFile => {
+                if Self::is_root() {
+                    return "/etc/jb/products.json".to_string().into();
+                }
+
+                env::var("XDG_CONFIG_HOME")
+                    .map_or_else(
+                        |_| PathBuf::from(env::var("HOME").expect("HOME environment variable not set")).join(".config"),
+                        PathBuf::from,
+                    )
+                    .join("jb/products.json")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
+            Variable::ConfigFile => {
+                if Self::is_root() {
+                    return "/etc/jb/config.json".to_string().into();
+                }
+
+                env::var("XDG_CONFIG_HOME")
+                    .map_or_else(
+                        |_| PathBuf::from(env::var("HOME").expect("HOME environment variable not set")).join(".config"),
+                        PathBuf::from,
+                    )
+                    .join("jb/config.json")
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+                    .into()
+            },
         }
     }
 
@@ -189,6 +424,24 @@ impl Variable {
             Variable::BinariesDirectory => "JB_BINARIES_DIR",
             Variable::DesktopDirectory => "JB_DESKTOP_DIR",
             Variable::SystemdDirectory => "JB_SYSTEMD_DIR",
+            Variable::JbrDirectory => "JB_JBR_DIR",
+            Variable::ApiUrl => "JB_API_URL",
+            Variable::AndroidStudioFeed => "JB_ANDROID_STUDIO_FEED",
+            Variable::JbrUrl => "JB_JBR_URL",
+            Variable::CacheDirectory => "JB_CACHE_DIR",
+            Variable::CacheTtl => "JB_CACHE_TTL",
+            Variable::Offline => "JB_OFFLINE",
+            Variable::ArchiveCacheDirectory => "JB_ARCHIVE_CACHE_DIR",
+            Variable::ArchiveCacheSize => "JB_ARCHIVE_CACHE_SIZE",
+            Variable::ExpansionFactor => "JB_EXPANSION_FACTOR",
+            Variable::CaBundle => "JB_CA_BUNDLE",
+            Variable::ConnectTimeout => "JB_CONNECT_TIMEOUT",
+            Variable::ReadTimeout => "JB_READ_TIMEOUT",
+            Variable::Retries => "JB_RETRIES",
+            Variable::SignaturePolicy => "JB_SIGNATURE_POLICY",
+            Variable::KeysDirectory => "JB_KEYS_DIR",
+            Variable::ProductsFile => "JB_PRODUCTS_FILE",
+            Variable::ConfigFile => "JB_CONFIG_FILE",
         }
     }
 
@@ -196,3 +449,38 @@ impl Variable {
         Uid::effective().is_root()
     }
 }
+
+/// Returns the values of the configuration file by key (see [`Variable::ConfigFile`]).
+///
+/// A missing configuration file has no values.
+///
+/// # Errors
+/// This function will return an error if the configuration file cannot be read or is invalid,
+/// in which case it is ignored by [`Variable::get`].
+pub fn config() -> anyhow::Result<&'static HashMap<String, String>> {
+    CONFIG.as_ref().map_err(|err| anyhow::anyhow!("{err:#}"))
+}
+
+/// Loads the configuration file, without logging anything, as logging itself reads variables.
+fn load_config() -> anyhow::Result<HashMap<String, String>> {
+    let path = Variable::ConfigFile.get::<PathBuf>();
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    let values = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    values.into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+                value => anyhow::bail!("Invalid value {value} for {key} in {}", path.display()),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
@@ -0,0 +1,173 @@
+//! Module for making HTTP requests.
+//!
+//! All requests should go through the clients built here, so that they share the same configuration:
+//! proxies (`HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`), an additional CA bundle, a User-Agent and timeouts.
+//! Transient errors can be retried with an exponential backoff using [`retry`] and [`retry_async`].
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::Context;
+use reqwest::{Certificate, StatusCode};
+use crate::env::Variable;
+
+/// The User-Agent sent with every request.
+pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"), " (+", env!("CARGO_PKG_REPOSITORY"), ")");
+
+/// The delay before the first retry, doubled after each attempt.
+const BACKOFF: Duration = Duration::from_millis(500);
+/// The maximum delay between two retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Builds an asynchronous HTTP client.
+///
+/// The read timeout is not applied to the whole request, as downloads may take a long time.
+/// Use [`read_timeout`] to apply it to each chunk of a response instead.
+///
+/// # Errors
+/// This function will return an error if the CA bundle cannot be loaded, or if the client cannot be built.
+pub fn client() -> anyhow::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(connect_timeout());
+
+    for certificate in certificates()? {
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder.build()
+        .with_context(|| "Failed to build HTTP client")
+}
+
+/// Builds a blocking HTTP client.
+///
+/// The read timeout is applied to the whole request, so this should only be used for small responses (e.g. API calls).
+///
+/// # Errors
+/// This function will return an error if the CA bundle cannot be loaded, or if the client cannot be built.
+pub fn blocking() -> anyhow::Result<reqwest::blocking::Client> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(connect_timeout())
+        .timeout(read_timeout());
+
+    for certificate in certificates()? {
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder.build()
+        .with_context(|| "Failed to build HTTP client")
+}
+
+/// Returns the timeout for connecting to a server.
+#[must_use]
+pub fn connect_timeout() -> Duration {
+    Duration::from_secs(Variable::ConnectTimeout.get_u64())
+}
+
+/// Returns the timeout for reading a response, or a chunk of a download.
+#[must_use]
+pub fn read_timeout() -> Duration {
+    Duration::from_secs(Variable::ReadTimeout.get_u64())
+}
+
+/// Runs a blocking request, retrying it with an exponential backoff on transient errors.
+///
+/// # Errors
+/// This function will return the last error if all attempts failed, or the first error that is not transient.
+pub fn retry<T, F>(mut request: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> anyhow::Result<T>,
+{
+    let mut attempt = 0;
+    loop {
+        match request() {
+            Err(err) if should_retry(&err, attempt) => {
+                std::thread::sleep(backoff(attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Runs an asynchronous request, retrying it with an exponential backoff on transient errors.
+///
+/// # Errors
+/// This function will return the last error if all attempts failed, or the first error that is not transient.
+pub async fn retry_async<T, F, Fut>(mut request: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(err) if should_retry(&err, attempt) => {
+                tokio::time::sleep(backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns whether an error is transient, meaning that the request may succeed if retried.
+///
+/// This includes connection errors, timeouts, server errors and rate limiting.
+#[must_use]
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout()
+                || err.is_connect()
+                || err.is_body()
+                || err.status().is_some_and(|status| status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::UnexpectedEof
+            );
+        }
+
+        false
+    })
+}
+
+fn should_retry(err: &anyhow::Error, attempt: u64) -> bool {
+    if attempt >= Variable::Retries.get_u64() || !is_transient(err) {
+        return false;
+    }
+
+    crate::debug!(
+        "Request failed, retrying in {} ({}/{}): {err:#}",
+        indicatif::HumanDuration(backoff(attempt)),
+        attempt + 1,
+        Variable::Retries.get_u64(),
+    );
+    true
+}
+
+fn backoff(attempt: u64) -> Duration {
+    let factor = 2u32.saturating_pow(u32::try_from(attempt).unwrap_or(u32::MAX));
+    BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+fn certificates() -> anyhow::Result<Vec<Certificate>> {
+    let bundle = Variable::CaBundle.get::<String>();
+    if bundle.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let path = PathBuf::from(bundle);
+    let pem = std::fs::read(&path)
+        .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+
+    Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("Failed to parse CA bundle {}", path.display()))
+}
@@ -10,6 +10,9 @@ pub mod log;
 pub mod error;
 pub mod tool;
 pub mod api;
+pub mod archive;
+pub mod http;
+pub mod verify;
 pub mod util;
 
 pub use error::{Batch, Result};
@@ -4,20 +4,30 @@
 
 use std::fmt::Display;
 use std::str::FromStr;
-use anyhow::Context;
 use crate::env::Variable;
 
 pub mod kind;
 pub mod version;
+pub mod constraint;
 pub mod build;
 pub mod release;
 pub mod action;
+pub mod metadata;
+pub mod product_info;
+pub mod product;
+pub mod selector;
+pub mod jbr;
 
 pub use action::*;
 pub use kind::Kind;
 pub use version::Version;
+pub use constraint::Constraint;
 pub use build::Build;
 pub use release::Type;
+pub use metadata::Metadata;
+pub use product_info::ProductInfo;
+pub use product::Product;
+pub use selector::{Scope, Selector};
 
 /// A tool.
 #[derive(serde::Serialize, Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
@@ -27,12 +37,21 @@ pub struct Tool {
     pub version: Option<Version>,
     pub build: Option<Build>,
     pub release: Option<Type>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<Constraint>,
 }
 
 impl Tool {
     #[must_use]
     pub fn new(kind: Kind, version: Option<Version>, build: Option<Build>, release: Option<Type>) -> Self {
-        Self { kind, version, build, release }
+        Self { kind, version, build, release, constraint: None }
+    }
+
+    /// Returns the tool with a constraint on its version or build number (see [`Constraint`]).
+    #[must_use]
+    pub fn with_constraint(mut self, constraint: Option<Constraint>) -> Self {
+        self.constraint = constraint;
+        self
     }
 
     #[must_use]
@@ -46,18 +65,21 @@ impl Tool {
     #[must_use]
     pub fn as_str(&self) -> String {
         let mut s = self.kind.as_str().to_string();
-        if self.version.is_some() || self.build.is_some() || self.release.is_some() {
+        if self.version.is_some() || self.build.is_some() || self.release.is_some() || self.constraint.is_some() {
             s.push('_');
         }
 
+        if let Some(constraint) = &self.constraint {
+            s.push_str(&format!("{constraint}"));
+        }
         if let Some(version) = &self.version {
-            s.push_str(&format!("{version}"));
+            s.push_str(&format!("{}{version}", if self.constraint.is_some() { "-" } else { "" }));
         }
         if let Some(build) = &self.build {
-            s.push_str(&format!("{}{build}", if self.version.is_some() { "-" } else { "" }));
+            s.push_str(&format!("{}{build}", if self.version.is_some() || self.constraint.is_some() { "-" } else { "" }));
         }
         if let Some(release) = &self.release {
-            s.push_str(&format!("{}{}", if self.version.is_some() || self.build.is_some() { "-" } else { "" }, release.as_str()));
+            s.push_str(&format!("{}{}", if self.version.is_some() || self.build.is_some() || self.constraint.is_some() { "-" } else { "" }, release.as_str()));
         }
 
         s
@@ -117,6 +139,13 @@ impl Tool {
             }
         }
 
+        // Match the constraint
+        if let Some(constraint) = &self.constraint {
+            if !constraint.matches(other.version.as_ref(), other.build.as_ref()) {
+                return false;
+            }
+        }
+
         // Match the release
         if let Some(release) = &self.release {
             if let Some(other_release) = &other.release {
@@ -213,8 +242,9 @@ impl Tool {
 
 impl Display for Tool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}{}",
+        write!(f, "{}{}{}{}",
                self.kind,
+               if let Some(constraint) = &self.constraint { format!(" {constraint}") } else { String::new() },
                if let Some(version) = &self.version { format!(" {version}") } else { String::new() },
                if let Some(build) = &self.build { format!(" {build}") } else { String::new() },
         )
@@ -230,15 +260,26 @@ impl FromStr for Tool {
         let kind = parts
             .next()
             .ok_or_else(|| anyhow::anyhow!("No tool kind found"))?;
-        let kind = kind.parse::<Kind>()
-            .with_context(|| format!("Failed to parse tool kind: {kind}"))?;
+        // The error already names the kind, and may suggest other kinds
+        let kind = kind.parse::<Kind>()?;
 
         let mut version = None;
         let mut build = None;
         let mut release = None;
 
         if let Some(part) = parts.next() {
-            let mut parts = part.split('-');
+            let mut parts = part.split('-').peekable();
+
+            // A constraint replaces the version and build, and may only be followed by a release type
+            if let Some(constraint) = parts.next_if(|part| Constraint::is_constraint(part)) {
+                let constraint = constraint.parse::<Constraint>()?;
+
+                if let Some(Ok(r)) = parts.next().map(str::parse::<Type>) {
+                    release = Some(r);
+                }
+
+                return Ok(Self::new(kind, None, None, release).with_constraint(Some(constraint)));
+            }
 
             // First try parsing a version
             if let Some(Ok(v)) = parts.next().map(str::parse::<Version>) {
@@ -6,7 +6,9 @@
 mod list;
 mod link;
 mod probe;
+mod install;
 
 pub use list::List;
 pub use link::Link;
-pub use probe::Probe;
\ No newline at end of file
+pub use probe::Probe;
+pub use install::Install;
\ No newline at end of file
@@ -0,0 +1,131 @@
+//! Install tools
+//!
+//! This module provides the ability to install tools atomically.
+//! Tools are extracted to a hidden staging directory next to the tools directory, verified, and only then moved into place,
+//! so that a partial or tampered install is never visible to the other actions.
+
+use std::path::{Path, PathBuf};
+use anyhow::Context;
+use crate::api::{Download, Target};
+use crate::env::Variable;
+use crate::tool::{Metadata, Tool};
+use crate::util::Archive;
+
+pub trait Install {
+    /// Returns the hidden staging directory where the tool is extracted before being installed.
+    fn as_staging(&self) -> PathBuf where Self: Sized;
+
+    /// Installs the tool from a download.
+    ///
+    /// The archive is downloaded and extracted to the staging directory, then verified against its checksum
+    /// and expected layout before being moved into place (see [`Install::install_with`]).
+    ///
+    /// # Errors
+    /// This function will return an error if the download, extraction or verification fails, or if the tool cannot be moved into place.
+    fn install(&self, download: &Download, metadata: &Metadata, progress: Option<&indicatif::ProgressBar>) -> anyhow::Result<()> where Self: Sized;
+
+    /// Installs the tool, using the given function to extract it to the staging directory and return the extracted archive.
+    ///
+    /// Once extracted, the layout of the tool is verified (its executable and icon must exist), its metadata is written
+    /// along with the archive (see [`Metadata::with_archive`]), and the staging directory is renamed to the tool's directory,
+    /// replacing any previous install.
+    /// The staging directory is removed if anything fails.
+    ///
+    /// # Errors
+    /// This function will return an error if the extraction or verification fails, or if the tool cannot be moved into place.
+    fn install_with<F>(&self, metadata: &Metadata, extract: F) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        F: FnOnce(&PathBuf) -> anyhow::Result<Archive>;
+}
+
+impl Install for Tool {
+    fn as_staging(&self) -> PathBuf {
+        Variable::ToolsDirectory.get::<PathBuf>()
+            .join(format!(".{}.staging", self.as_str()))
+    }
+
+    fn install(&self, download: &Download, metadata: &Metadata, progress: Option<&indicatif::ProgressBar>) -> anyhow::Result<()> {
+        self.install_with(metadata, |staging| {
+            crate::util::download_extract(&download.link, staging, download.checksum(), download.size(), progress)
+        })
+    }
+
+    fn install_with<F>(&self, metadata: &Metadata, extract: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(&PathBuf) -> anyhow::Result<Archive>,
+    {
+        let staging = self.as_staging();
+
+        // Clean up any leftover from an interrupted install
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clean up {}", staging.display()))?;
+        }
+
+        let result = extract(&staging)
+            .and_then(|archive| {
+                self.verify_layout(&staging, &metadata.target)?;
+                metadata.clone().with_archive(&archive).write_to(&staging)
+            })
+            .and_then(|()| self.commit(&staging));
+
+        if result.is_err() && staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clean up {}", staging.display()))?;
+        }
+
+        result
+    }
+}
+
+impl Tool {
+    /// Verifies that an extracted tool has the expected layout.
+    ///
+    /// Tools built for another platform are packaged differently, so only the ones for the current platform are verified.
+    fn verify_layout(&self, folder: &Path, target: &Target) -> anyhow::Result<()> {
+        if target.platform != Target::host().platform {
+            crate::debug!("Skipping layout verification for {target}");
+            return Ok(());
+        }
+
+        for file in [self.kind.as_executable(), self.kind.as_icon()] {
+            if !folder.join(&file).exists() {
+                anyhow::bail!("Unexpected layout for {}: {file} is missing", self.as_str());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves a verified staging directory into place, replacing any previous install.
+    fn commit(&self, staging: &Path) -> anyhow::Result<()> {
+        let path = self.as_path();
+
+        if !path.exists() {
+            return std::fs::rename(staging, &path)
+                .with_context(|| format!("Failed to move {} to {}", staging.display(), path.display()));
+        }
+
+        // Directories cannot be atomically replaced, so move the previous install aside first and restore it on failure
+        let previous = Variable::ToolsDirectory.get::<PathBuf>()
+            .join(format!(".{}.previous", self.as_str()));
+        if previous.exists() {
+            std::fs::remove_dir_all(&previous)
+                .with_context(|| format!("Failed to clean up {}", previous.display()))?;
+        }
+
+        std::fs::rename(&path, &previous)
+            .with_context(|| format!("Failed to move {} to {}", path.display(), previous.display()))?;
+
+        if let Err(err) = std::fs::rename(staging, &path) {
+            std::fs::rename(&previous, &path)
+                .with_context(|| format!("Failed to restore {}", path.display()))?;
+            return Err(err)
+                .with_context(|| format!("Failed to move {} to {}", staging.display(), path.display()));
+        }
+
+        std::fs::remove_dir_all(&previous)
+            .with_context(|| format!("Failed to clean up {}", previous.display()))
+    }
+}
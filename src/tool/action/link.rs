@@ -17,7 +17,7 @@ pub trait Link {
     /// This creates a symlink to the tool's binary and icon in the PATH and icons directory, respectively.
     ///
     /// # Errors
-    /// This function will return an error if the tool is already linked, or if the symlinks fail.
+    /// This function will return an error if the tool was built for another platform or architecture, or if the symlinks fail.
     fn link(&self) -> anyhow::Result<()> where Self: Sized;
 
     /// Unlinks the tool.
@@ -74,6 +74,12 @@ impl Link for Tool {
             return Ok(());
         }
 
+        if let Some(metadata) = self.metadata() {
+            if !metadata.target.is_host() {
+                anyhow::bail!("{} was installed for {}, and cannot be linked on this host", self.as_str(), metadata.target);
+            }
+        }
+
         let executable_path = self.as_executable();
         let binaries_directory = Variable::BinariesDirectory.get::<PathBuf>();
 
@@ -125,7 +131,7 @@ impl Link for Tool {
         // Find an alternative version to link
         let mut tools = Tool::list_kind(self.kind)
             .with_context(|| format!("Failed to list installed tools for {}", self.kind))?;
-        tools.retain(|tool| tool != self);
+        tools.retain(|tool| tool != self && tool.is_native());
         tools.sort();
 
         if let Some(tool) = tools.first() {
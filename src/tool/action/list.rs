@@ -6,7 +6,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use crate::env::Variable;
-use crate::tool::{Tool, kind::Kind};
+use crate::tool::{Metadata, Tool, kind::Kind};
 
 pub trait List {
     /// Lists all tools.
@@ -28,6 +28,12 @@ pub trait List {
     /// # Errors
     /// This function will return an error if the tools directory does not exist or if the tools cannot be listed.
     fn list_matching(&self) -> anyhow::Result<Vec<Tool>> where Self: Sized;
+
+    /// Lists all tools, along with the metadata recorded when they were installed, if any (see [`Metadata`]).
+    ///
+    /// # Errors
+    /// This function will return an error if the tools directory does not exist or if the tools cannot be listed.
+    fn list_with_metadata() -> anyhow::Result<Vec<(Tool, Option<Metadata>)>> where Self: Sized;
 }
 
 impl List for Tool {
@@ -42,10 +48,17 @@ impl List for Tool {
             if path.is_dir() {
                 let name= path.file_name().unwrap().to_str().unwrap();
 
-                if let Ok(tool) = Tool::from_str(name) {
-                    tools.push(tool);
-                } else {
-                    crate::debug!("Skipping invalid tool directory: {name}");
+                // Hidden directories are used for staging installs
+                if name.starts_with('.') {
+                    continue;
+                }
+
+                // Tool directories are always named after the slug of their kind, never an alias of it
+                match Tool::from_str(name) {
+                    Ok(tool) if name.split('_').next() == Some(tool.kind.as_str()) => tools.push(tool),
+                    _ => {
+                        crate::debug!("Skipping invalid tool directory: {name}");
+                    }
                 }
             }
         }
@@ -70,4 +83,16 @@ impl List for Tool {
                 .collect()
         )
     }
+
+    fn list_with_metadata() -> anyhow::Result<Vec<(Tool, Option<Metadata>)>> {
+        Ok(
+            Self::list()?
+                .into_iter()
+                .map(|tool| {
+                    let metadata = tool.metadata();
+                    (tool, metadata)
+                })
+                .collect()
+        )
+    }
 }
\ No newline at end of file
@@ -1,57 +1,82 @@
 //! Build version types and parsing
 //!
 //! This module contains types and parsing for build version numbers.
+//! A build has any number of components (e.g. 241.14494.240.1) and an optional qualifier (e.g. 242.SNAPSHOT).
 
+use std::cmp::Ordering;
 use std::fmt::Display;
 use std::str::FromStr;
 use anyhow::Context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use super::version::{compare_qualifiers, normalize_qualifier, parse_components, qualifier_eq};
 
 /// A build version number
-#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[readonly::make]
 pub struct Build {
-    pub major: u16,
-    pub minor: u16,
-    pub patch: Option<u16>,
+    /// The components of the build (e.g. 241, 14494 and 240 in 241.14494.240)
+    pub components: Vec<u32>,
+    /// The qualifier of the build, used for development builds (e.g. SNAPSHOT)
+    pub qualifier: Option<String>,
 }
 
 impl Build {
+    /// Creates a build from its components, which must not be empty, normalizing its qualifier.
+    ///
+    /// # Panics
+    /// This function will panic if there are no components.
     #[must_use]
-    pub fn new(major: u16, minor: u16, patch: Option<u16>) -> Self {
-        Self { major, minor, patch }
+    pub fn new(components: Vec<u32>, qualifier: Option<String>) -> Self {
+        assert!(!components.is_empty(), "A build must have at least one component");
+        Self { components, qualifier: normalize_qualifier(qualifier) }
+    }
+
+    /// Returns the branch of the build (e.g. 241 in 241.14494.240).
+    #[must_use]
+    pub fn major(&self) -> u32 {
+        self.components[0]
     }
 
     /// Returns whether the build version matches another build version.
     ///
-    /// This will return `true` if the major and minor versions match and the patch version matches if it is present.
-    ///
-    /// # Panics
-    /// This function will panic if the build version is not valid.
+    /// The other build matches if it starts with the same components (e.g. 241.14494 matches 241.14494.240),
+    /// and has the same qualifier if this build has one.
     #[must_use]
     pub fn matched(&self, other: &Self) -> bool {
-        if self.major != other.major {
+        if !other.components.starts_with(&self.components) {
             return false;
         }
-        if self.minor != other.minor {
-            return false;
-        }
-        if self.patch.is_some() {
-            if other.patch.is_none() {
-                return false;
-            }
-            if self.patch.unwrap() != other.patch.unwrap() {
-                return false;
-            }
-        }
 
-        true
+        self.qualifier.is_none() || qualifier_eq(self.qualifier.as_deref(), other.qualifier.as_deref())
+    }
+}
+
+impl Ord for Build {
+    /// Builds are ordered by their components, and a qualified build comes before the same unqualified build.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.components.cmp(&other.components)
+            .then_with(|| compare_qualifiers(self.qualifier.as_deref(), other.qualifier.as_deref()))
+    }
+}
+
+impl PartialOrd for Build {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl Display for Build {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}{}", self.major, self.minor, self.patch.map_or(String::new(), |p| format!(".{p}")))
+        let components = self.components.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", components.join("."))?;
+        if let Some(qualifier) = &self.qualifier {
+            write!(f, ".{qualifier}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -59,29 +84,24 @@ impl FromStr for Build {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.splitn(3, '.');
-        let major = parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("No major version found"))?;
-        let minor = parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("No minor version found"))?;
-
-        let patch = parts
-            .next();
-
-        let major = major.parse::<u16>()
-            .with_context(|| format!("Failed to parse major version: {major}"))?;
-        let minor = minor.parse::<u16>()
-            .with_context(|| format!("Failed to parse minor version: {minor}"))?;
-
-        if let Some(patch) = patch {
-            let patch = patch.parse::<u16>()
-                .with_context(|| format!("Failed to parse patch version: {patch}"))?;
-            Ok(Self::new(major, minor, Some(patch)))
-        } else {
-            Ok(Self::new(major, minor, None))
+        let (components, qualifier) = parse_components(s)
+            .with_context(|| format!("Invalid build: {s}"))?;
+
+        // A single component is only a build when qualified (e.g. 242.SNAPSHOT), otherwise it is most likely a typo
+        if components.len() < 2 && qualifier.is_none() {
+            anyhow::bail!("Invalid build: {s} (expected at least two components, e.g. 241.14494)");
         }
+
+        Ok(Self::new(components, qualifier))
+    }
+}
+
+impl Serialize for Build {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
     }
 }
 
@@ -93,4 +113,4 @@ impl<'de> Deserialize<'de> for Build {
         let s = String::deserialize(deserializer)?;
         Self::from_str(&s).map_err(serde::de::Error::custom)
     }
-}
\ No newline at end of file
+}
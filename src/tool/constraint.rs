@@ -0,0 +1,215 @@
+//! Version constraint types and parsing
+//!
+//! This module contains constraints on the version or build number of tools, used to match a range of releases
+//! or installed tools (e.g. `>=2023.3,<2024.2`, `~2024.1`, `2024.*` or `>=241.15000`).
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::str::FromStr;
+use anyhow::Context;
+use serde::Serialize;
+use crate::tool::{Build, Tool, Version};
+
+/// The characters that can only be found in constraints, and not in plain versions.
+const OPERATORS: &[char] = &['<', '>', '=', '~', '*', ','];
+
+/// The smallest first component of a version, as versions start with a year while builds start with a branch (e.g. 241).
+const MIN_YEAR: u32 = 1000;
+
+/// A constraint on the version or build number of a tool, made of comparators that must all match.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Constraint {
+    comparators: Vec<Comparator>,
+}
+
+/// A single comparison, against either a version or a build number.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Comparator {
+    op: Op,
+    field: Field,
+    /// The components that are compared, any missing component matches anything (e.g. 2024.1 matches 2024.1.2)
+    parts: Vec<u32>,
+}
+
+/// The operator of a comparator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Op {
+    /// Starts with the given components (e.g. `2024.1` or `=2024.1`)
+    Exact,
+    /// Starts with the components before the wildcard (e.g. `2024.*`)
+    Wildcard,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    /// At least the given components, within the same major version (e.g. `~2024.1.2` is `>=2024.1.2,<2024.2`)
+    Tilde,
+}
+
+/// What a comparator applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Field {
+    /// A version (e.g. 2024.1.2), whose major version is its first two components
+    Version,
+    /// A build number (e.g. 241.15989.150), whose major version is its first component
+    Build,
+}
+
+impl Constraint {
+    /// Returns whether a string uses the constraint syntax, instead of being a plain version or build number.
+    #[must_use]
+    pub fn is_constraint(s: &str) -> bool {
+        s.contains(OPERATORS)
+    }
+
+    /// Returns whether a version and build number match the constraint.
+    ///
+    /// A comparator on a missing version or build number never matches.
+    #[must_use]
+    pub fn matches(&self, version: Option<&Version>, build: Option<&Build>) -> bool {
+        self.comparators.iter().all(|comparator| {
+            match comparator.field {
+                Field::Version => version.is_some_and(|version| comparator.matches(&version.parts())),
+                Field::Build => build.is_some_and(|build| comparator.matches(&build.components)),
+            }
+        })
+    }
+
+    /// Returns the constraint that a tool puts on versions and build numbers, if any.
+    ///
+    /// The tool's version and build number must match exactly, ignoring their qualifiers, and any missing component
+    /// matches anything (e.g. a tool with version 2024.1 matches 2024.1.2). The tool's own constraint must match too.
+    #[must_use]
+    pub fn for_tool(tool: &Tool) -> Option<Self> {
+        let version = tool.version.iter()
+            .map(|version| Comparator { op: Op::Exact, field: Field::Version, parts: version.parts() });
+        let build = tool.build.iter()
+            .map(|build| Comparator { op: Op::Exact, field: Field::Build, parts: build.components.clone() });
+
+        let comparators = tool.constraint.iter()
+            .flat_map(|constraint| constraint.comparators.iter().cloned())
+            .chain(version)
+            .chain(build)
+            .collect::<Vec<_>>();
+
+        (!comparators.is_empty()).then_some(Self { comparators })
+    }
+}
+
+impl Comparator {
+    fn matches(&self, value: &[u32]) -> bool {
+        let ordering = compare(value, &self.parts);
+
+        match self.op {
+            Op::Exact | Op::Wildcard => ordering == Ordering::Equal,
+            Op::Greater => ordering == Ordering::Greater,
+            Op::GreaterEq => ordering != Ordering::Less,
+            Op::Less => ordering == Ordering::Less,
+            Op::LessEq => ordering != Ordering::Greater,
+            Op::Tilde => {
+                let major = match self.field {
+                    Field::Version => 2,
+                    Field::Build => 1,
+                }.min(self.parts.len());
+
+                compare(value, &self.parts[..major]) == Ordering::Equal && ordering != Ordering::Less
+            }
+        }
+    }
+}
+
+/// Compares a value to the components of a comparator, only up to the number of components of the comparator.
+/// Missing components of the value are considered to be 0.
+fn compare(value: &[u32], parts: &[u32]) -> Ordering {
+    parts.iter()
+        .enumerate()
+        .map(|(index, part)| value.get(index).copied().unwrap_or_default().cmp(part))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let comparators = self.comparators.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", comparators.join(","))
+    }
+}
+
+impl Display for Comparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = self.parts.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        match self.op {
+            Op::Exact => write!(f, "{parts}"),
+            Op::Wildcard => write!(f, "{parts}.*"),
+            Op::Greater => write!(f, ">{parts}"),
+            Op::GreaterEq => write!(f, ">={parts}"),
+            Op::Less => write!(f, "<{parts}"),
+            Op::LessEq => write!(f, "<={parts}"),
+            Op::Tilde => write!(f, "~{parts}"),
+        }
+    }
+}
+
+impl FromStr for Constraint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let comparators = s.split(',')
+            .map(|comparator| Comparator::from_str(comparator.trim()))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|err| anyhow::anyhow!("Invalid constraint {s}: {err:#}"))?;
+
+        Ok(Self { comparators })
+    }
+}
+
+impl FromStr for Comparator {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+        ]
+            .into_iter()
+            .find_map(|(prefix, op)| s.strip_prefix(prefix).map(|rest| (op, rest.trim())))
+            .unwrap_or((Op::Exact, s));
+
+        let (op, rest) = match rest.strip_suffix(".*") {
+            Some(rest) if op == Op::Exact => (Op::Wildcard, rest),
+            Some(_) => anyhow::bail!("Wildcards cannot be used with an operator: {s}"),
+            None => (op, rest),
+        };
+
+        if rest.is_empty() {
+            anyhow::bail!("Empty comparator");
+        }
+
+        let parts = rest.split('.')
+            .map(|part| part.parse::<u32>().with_context(|| format!("Failed to parse {part} in {s}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let field = if parts[0] >= MIN_YEAR { Field::Version } else { Field::Build };
+
+        Ok(Self { op, field, parts })
+    }
+}
+
+impl Serialize for Constraint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        self.to_string().serialize(serializer)
+    }
+}
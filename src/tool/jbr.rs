@@ -0,0 +1,328 @@
+//! `JetBrains` Runtime
+//!
+//! This module contains standalone builds of the `JetBrains` Runtime (JBR), the JDK that `IntelliJ`-based tools run on.
+//! Runtimes are installed next to the tools directory (see [`Variable::JbrDirectory`]).
+//! A tool runs on another runtime than its bundled one when the `<binary>.jdk` file of its configuration directory
+//! points to it, which is read by the tool's launcher.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use anyhow::Context;
+use serde::Serialize;
+use crate::api::Target;
+use crate::archive::Format;
+use crate::env::Variable;
+use crate::tool::{ProductInfo, Tool};
+use crate::util::Checksum;
+
+/// The flavor installed when none is specified, which is the one bundled with the tools.
+pub const DEFAULT_FLAVOR: &str = "jbr_jcef";
+
+/// A build of the `JetBrains` Runtime.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Runtime {
+    /// The flavor of the build (e.g. `jbr_jcef`, `jbr`, `jbrsdk`)
+    pub flavor: String,
+    /// The version of the JDK (e.g. 21.0.4)
+    pub version: String,
+    /// The build number of the runtime (e.g. 509.17)
+    pub build: String,
+}
+
+impl Runtime {
+    /// Returns the runtime as a string, which is also the name of its directory (e.g. `jbr_jcef-21.0.4-b509.17`).
+    #[must_use]
+    pub fn as_str(&self) -> String {
+        format!("{}-{}-b{}", self.flavor, self.version, self.build)
+    }
+
+    /// Returns the path to the runtime.
+    ///
+    /// **Note:** This does not check if the runtime actually exists.
+    #[must_use]
+    pub fn as_path(&self) -> PathBuf {
+        directory().join(self.as_str())
+    }
+
+    /// Returns the path to the runtime's java executable.
+    #[must_use]
+    pub fn as_java(&self) -> PathBuf {
+        self.as_path().join("bin/java")
+    }
+
+    /// Returns whether the runtime is installed.
+    #[must_use]
+    pub fn is_installed(&self) -> bool {
+        self.as_java().exists()
+    }
+
+    /// Returns the download link of the runtime for a target platform and architecture.
+    ///
+    /// The link follows the naming of `JetBrains`' runtime archives
+    /// (e.g. `jbr_jcef-21.0.4-linux-x64-b509.17.tar.gz`), under the base URL of [`Variable::JbrUrl`].
+    #[must_use]
+    pub fn link(&self, target: &Target) -> String {
+        let platform = match target.platform.as_str() {
+            "mac" => "osx",
+            platform => platform,
+        };
+        let arch = match target.arch.as_str() {
+            "x86_64" => "x64",
+            arch => arch,
+        };
+
+        format!(
+            "{}/{}-{}-{platform}-{arch}-b{}.tar.gz",
+            Variable::JbrUrl.get::<String>().trim_end_matches('/'),
+            self.flavor,
+            self.version,
+            self.build,
+        )
+    }
+
+    /// Lists the installed runtimes, from the oldest to the newest.
+    ///
+    /// Any directories that are not valid runtimes will be skipped. (logged as debug)
+    ///
+    /// # Errors
+    /// This function will return an error if the runtimes directory cannot be listed.
+    pub fn list() -> anyhow::Result<Vec<Self>> {
+        let directory = directory();
+        if !directory.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut runtimes = vec![];
+
+        for entry in std::fs::read_dir(&directory)
+            .with_context(|| format!("Failed to list {}", directory.display()))? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            // Hidden directories are used for staging installs
+            if name.starts_with('.') || !path.is_dir() {
+                continue;
+            }
+
+            match Self::from_str(name) {
+                Ok(runtime) if runtime.as_str() == name => runtimes.push(runtime),
+                _ => crate::debug!("Skipping invalid runtime directory: {name}"),
+            }
+        }
+
+        runtimes.sort();
+        Ok(runtimes)
+    }
+
+    /// Installs the runtime for the host, replacing any previous install.
+    ///
+    /// The archive is verified against the checksum published next to it (e.g. `jbr_jcef-21.0.4-linux-x64-b509.17.tar.gz.checksum`),
+    /// then extracted to a hidden staging directory, and only moved into place once its java executable is found.
+    ///
+    /// # Errors
+    /// This function will return an error if the download, checksum verification or extraction fails,
+    /// if the archive is not a runtime, or if the runtime cannot be moved into place.
+    pub fn install(&self, progress: Option<&indicatif::ProgressBar>) -> anyhow::Result<()> {
+        let directory = directory();
+        std::fs::create_dir_all(&directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+        let staging = directory.join(format!(".{}.staging", self.as_str()));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clean up {}", staging.display()))?;
+        }
+
+        let link = self.link(&Target::host());
+        let checksum = format!("{link}.checksum");
+        let result = crate::util::download_extract(&link, &staging, Some(Checksum::Link(&checksum)), None, progress)
+            .and_then(|_| {
+                if staging.join("bin/java").exists() {
+                    Ok(())
+                } else {
+                    anyhow::bail!("Unexpected layout for {}: bin/java is missing", self.as_str())
+                }
+            })
+            .and_then(|()| {
+                let path = self.as_path();
+                if path.exists() {
+                    std::fs::remove_dir_all(&path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                std::fs::rename(&staging, &path)
+                    .with_context(|| format!("Failed to move {} to {}", staging.display(), path.display()))
+            });
+
+        if result.is_err() && staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clean up {}", staging.display()))?;
+        }
+
+        result
+    }
+}
+
+impl Display for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Runtime {
+    type Err = anyhow::Error;
+
+    /// Parses a runtime from its directory name (e.g. `jbr_jcef-21.0.4-b509.17`), its archive name
+    /// (e.g. `jbr_jcef-21.0.4-linux-x64-b509.17.tar.gz`) or its release tag (e.g. `21.0.4b509.17`, `jbr-release-21.0.4b509.17`).
+    ///
+    /// Release tags use the default flavor (see [`DEFAULT_FLAVOR`]).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut name = s.trim();
+        if let Some(format) = Format::from_name(name) {
+            name = format.extensions().iter()
+                .find_map(|extension| name.strip_suffix(extension))
+                .unwrap_or(name);
+        }
+
+        let (flavor, version, build) = if let Some(tag) = name.strip_prefix("jbr-release-").or_else(|| (!name.contains('-')).then_some(name)) {
+            let (version, build) = tag.split_once('b')
+                .ok_or_else(|| anyhow::anyhow!("Invalid runtime {s}: no build number found (e.g. 21.0.4b509.17)"))?;
+            (DEFAULT_FLAVOR, version, build)
+        } else {
+            let parts = name.split('-').collect::<Vec<_>>();
+            let [flavor, version, .., build] = parts.as_slice() else {
+                anyhow::bail!("Invalid runtime {s} (e.g. jbr_jcef-21.0.4-b509.17)");
+            };
+            let build = build.strip_prefix('b')
+                .ok_or_else(|| anyhow::anyhow!("Invalid runtime {s}: no build number found (e.g. jbr_jcef-21.0.4-b509.17)"))?;
+            (*flavor, *version, build)
+        };
+
+        if !flavor.starts_with("jbr") || !flavor.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("Invalid runtime flavor {flavor} (e.g. {DEFAULT_FLAVOR})");
+        }
+        for (name, value) in [("version", version), ("build", build)] {
+            if value.is_empty() || !value.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())) {
+                anyhow::bail!("Invalid runtime {name} {value}");
+            }
+        }
+
+        Ok(Self {
+            flavor: flavor.to_string(),
+            version: version.to_string(),
+            build: build.to_string(),
+        })
+    }
+}
+
+impl Ord for Runtime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let numbers = |value: &str| value.split('.')
+            .map(|part| part.parse::<u32>().unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        numbers(&self.version).cmp(&numbers(&other.version))
+            .then_with(|| numbers(&self.build).cmp(&numbers(&other.build)))
+            .then_with(|| self.flavor.cmp(&other.flavor))
+    }
+}
+
+impl PartialOrd for Runtime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns the directory where runtimes are installed.
+#[must_use]
+pub fn directory() -> PathBuf {
+    Variable::JbrDirectory.get::<PathBuf>()
+}
+
+/// Returns the configuration directory of an installed tool (e.g. ~/.config/JetBrains/IntelliJIdea2024.1).
+///
+/// The directory is named after the `dataDirectoryName` of the tool's `product-info.json`, under the directory of its vendor.
+///
+/// # Errors
+/// This function will return an error if the product information of the tool cannot be read, or has no data directory name.
+pub fn config_directory(tool: &Tool) -> anyhow::Result<PathBuf> {
+    let info = ProductInfo::read(&tool.as_path())?;
+    let name = info.data_directory_name
+        .with_context(|| format!("{} has no configuration directory", tool.as_str()))?;
+
+    let home = std::env::var("XDG_CONFIG_HOME")
+        .map_or_else(
+            |_| PathBuf::from(std::env::var("HOME").expect("HOME environment variable not set")).join(".config"),
+            PathBuf::from,
+        );
+
+    Ok(home
+        .join(info.product_vendor.as_deref().unwrap_or("JetBrains"))
+        .join(name))
+}
+
+/// Returns the path of the file that overrides the runtime of a tool (e.g. ~/.config/JetBrains/IntelliJIdea2024.1/idea.jdk).
+///
+/// # Errors
+/// This function will return an error if the configuration directory of the tool cannot be determined.
+pub fn override_path(tool: &Tool) -> anyhow::Result<PathBuf> {
+    let executable = tool.kind.as_executable();
+    let binary = Path::new(&executable)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(tool.kind.binary());
+
+    Ok(config_directory(tool)?.join(format!("{binary}.jdk")))
+}
+
+/// Returns the runtime a tool is overridden to run on, if any.
+///
+/// # Errors
+/// This function will return an error if the override file cannot be found or read.
+pub fn current(tool: &Tool) -> anyhow::Result<Option<PathBuf>> {
+    let path = override_path(tool)?;
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) if content.trim().is_empty() => Ok(None),
+        Ok(content) => Ok(Some(PathBuf::from(content.trim()))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Overrides the runtime of a tool, which takes effect the next time it is launched.
+///
+/// # Errors
+/// This function will return an error if the runtime is not installed, or if the override file cannot be written.
+pub fn set(tool: &Tool, runtime: &Runtime) -> anyhow::Result<()> {
+    if !runtime.is_installed() {
+        anyhow::bail!("{runtime} is not installed");
+    }
+
+    let path = override_path(tool)?;
+    let directory = path.parent().unwrap();
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+    std::fs::write(&path, format!("{}\n", runtime.as_path().display()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Removes the runtime override of a tool, so that it runs on its bundled runtime again.
+///
+/// Returns whether the tool was overridden.
+///
+/// # Errors
+/// This function will return an error if the override file cannot be removed.
+pub fn reset(tool: &Tool) -> anyhow::Result<bool> {
+    let path = override_path(tool)?;
+
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
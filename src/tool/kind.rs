@@ -1,70 +1,45 @@
 //! `JetBrains` tool kinds and parsing
 //!
 //! This module contains types and parsing for `JetBrains` tool kinds.
+//! A kind is a lookup into the product registry (see [`super::product`]), so new products do not require a code change.
+//! Kinds are parsed from their slug, or from any other name of the product (see [`Kind::names`]), ignoring case.
 
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use serde::Serialize;
 use crate::Tool;
-use super::List;
+use crate::api::Provider;
+use super::{List, Product, Type};
+use super::product::normalize;
+
+/// The maximum number of kinds suggested when parsing an unknown kind.
+const MAX_SUGGESTIONS: usize = 3;
 
 /// The tool kind.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Kind {
-    IntelliJIdeaUltimate,
-    IntelliJIdeaCommunity,
-    PyCharmProfessional,
-    PyCharmCommunity,
-    PhpStorm,
-    GoLand,
-    Rider,
-    CLion,
-    RustRover,
-    WebStorm,
-    RubyMine,
-    DataGrip,
-    DataSpell,
-    Fleet,
-    Aqua,
-    Writerside,
-    DotMemory,
-    DotTrace,
-    MPS,
-
-    Space,
-    Gateway,
-}
+#[derive(Clone, Copy)]
+pub struct Kind(&'static Product);
 
 impl Kind {
+    /// Creates a tool kind from a registered product.
+    #[must_use]
+    pub(crate) fn new(product: &'static Product) -> Self {
+        Self(product)
+    }
+
     /// Returns a list of all tool kinds.
     ///
     /// This is used for ordering and display purposes.
-    /// The list is static and does not require any allocations.
+    /// The list is loaded once from the product registry, and does not require any further allocations.
     #[must_use]
     pub fn list() -> &'static [Self] {
-        &[
-            Self::IntelliJIdeaUltimate,
-            Self::IntelliJIdeaCommunity,
-            Self::PyCharmProfessional,
-            Self::PyCharmCommunity,
-            Self::PhpStorm,
-            Self::GoLand,
-            Self::Rider,
-            Self::CLion,
-            Self::RustRover,
-            Self::WebStorm,
-            Self::RubyMine,
-            Self::DataGrip,
-            Self::DataSpell,
-            Self::Fleet,
-            Self::Aqua,
-            Self::Writerside,
-            Self::DotMemory,
-            Self::DotTrace,
-            Self::MPS,
-            Self::Space,
-            Self::Gateway,
-        ]
+        super::product::registry()
+    }
+
+    /// Get the product this tool kind points to.
+    #[must_use]
+    pub fn product(&self) -> &'static Product {
+        self.0
     }
 
     /// Get the binary name for this tool kind.
@@ -72,66 +47,54 @@ impl Kind {
     /// This is used to determine the binary name for a tool kind and symbolically link it to the correct binary.
     #[must_use]
     pub fn binary(&self) -> &'static str {
-        match self {
-            Self::IntelliJIdeaUltimate | Self::IntelliJIdeaCommunity => "idea",
-            Self::PyCharmProfessional | Self::PyCharmCommunity => "pycharm",
-            Self::Fleet => "Fleet",
-            _ => self.as_str(),
-        }
+        &self.0.binary
     }
 
     /// Get the relative path to the executable for this tool kind.
     ///
     /// This is used to determine the binary path for a tool kind and symbolically link it to the correct binary.
-    #[allow(clippy::single_match_else)]
     #[must_use]
     pub fn as_executable(&self) -> String {
-        match self {
-            Self::Fleet => format!("bin/{}", self.binary()),
-            _ => format!("bin/{}.sh", self.binary()),
-        }
+        self.0.executable.clone()
     }
 
     /// Get the relative path to the icon for this tool kind.
     ///
     /// This is used to determine the icon path for a tool kind and symbolically link it to the correct icon.
-    #[allow(clippy::single_match_else)]
     #[must_use]
     pub fn as_icon(&self) -> String {
-        match self {
-            Self::Fleet => format!("lib/{}.png", self.binary()),
-            _ => format!("bin/{}.svg", self.binary()),
-        }
+        self.0.icon.clone()
     }
 
-    /// Get the tool kind as a string (e.g. "intellij-idea-ultimate", "pycharm-professional").
+    /// Get the tool kind as a string (e.g. "idea-ultimate", "pycharm-professional").
     ///
     /// This returns the same string as the `FromStr` implementation.
     #[must_use]
     pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::IntelliJIdeaUltimate => "idea-ultimate",
-            Self::IntelliJIdeaCommunity => "idea-community",
-            Self::PyCharmProfessional => "pycharm-professional",
-            Self::PyCharmCommunity => "pycharm-community",
-            Self::PhpStorm => "phpstorm",
-            Self::GoLand => "goland",
-            Self::Rider => "rider",
-            Self::CLion => "clion",
-            Self::RustRover => "rustrover",
-            Self::WebStorm => "webstorm",
-            Self::RubyMine => "rubymine",
-            Self::DataGrip => "datagrip",
-            Self::DataSpell => "dataspell",
-            Self::Fleet => "fleet",
-            Self::Aqua => "aqua",
-            Self::Writerside => "writerside",
-            Self::DotMemory => "dotmemory",
-            Self::DotTrace => "dottrace",
-            Self::MPS => "mps",
-            Self::Space => "space",
-            Self::Gateway => "gateway",
-        }
+        &self.0.slug
+    }
+
+    /// Get every name this tool kind can be parsed from, by order of precedence.
+    ///
+    /// These are the slug, the aliases, the codes, the binary name and the display name of the product
+    /// (e.g. "idea-ultimate", "intellij", "IIU", "IU", "idea" and "IntelliJ IDEA Ultimate").
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.ranked_names()
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Get every name of this tool kind, along with its precedence (the lower, the stronger).
+    fn ranked_names(&self) -> impl Iterator<Item = (u8, &'static str)> {
+        let product = self.0;
+
+        [(0, product.slug.as_str())].into_iter()
+            .chain(product.aliases.iter().map(|alias| (1, alias.as_str())))
+            .chain([(2, product.code.as_str())])
+            .chain(product.product_code.as_deref().map(|code| (2, code)))
+            .chain([(3, product.binary.as_str()), (4, product.name.as_str())])
+            .filter(|(_, name)| !name.is_empty())
     }
 
     /// Get the tool kind as a code (e.g. "IIU", "IIC").
@@ -139,60 +102,44 @@ impl Kind {
     /// This is used to fetch releases from `JetBrains`' API.
     #[must_use]
     pub fn code(&self) -> &'static str {
-        match self {
-            Self::IntelliJIdeaUltimate => "IIU",
-            Self::IntelliJIdeaCommunity => "IIC",
-            Self::PyCharmProfessional => "PCP",
-            Self::PyCharmCommunity => "PCC",
-            Self::PhpStorm => "PS",
-            Self::GoLand => "GO",
-            Self::Rider => "RD",
-            Self::CLion => "CL",
-            Self::RustRover => "RR",
-            Self::WebStorm => "WS",
-            Self::RubyMine => "RM",
-            Self::DataGrip => "DG",
-            Self::DataSpell => "DS",
-            Self::Fleet => "FL",
-            Self::Aqua => "QA",
-            Self::Writerside => "WRS",
-            Self::DotMemory => "DM",
-            Self::DotTrace => "DP",
-            Self::MPS => "MPS",
-
-            Self::Space => "SPA",
-            Self::Gateway => "GW",
-        }
+        &self.0.code
+    }
+
+    /// Get the tool kind from a code used by `JetBrains`' API (e.g. "IIU").
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::list().iter().find(|kind| kind.code() == code).copied()
+    }
+
+    /// Get the tool kind from a product code, as found in `product-info.json` (e.g. "IU", "PY").
+    ///
+    /// These codes differ from the ones used by `JetBrains`' API for some tool kinds.
+    /// Tools that are not based on the `IntelliJ` platform have no such code.
+    #[must_use]
+    pub fn from_product_code(code: &str) -> Option<Self> {
+        Self::list().iter()
+            .find(|kind| kind.0.product_code.as_deref() == Some(code))
+            .copied()
     }
 
     /// Get the tool kind as a human-readable description.
     ///
-    /// These descriptions are directly from the `JetBrains` website.
     /// This is used to display a description in desktop entries.
     #[must_use]
     pub fn description(&self) -> &'static str {
-        match self {
-            Self::IntelliJIdeaCommunity | Self::IntelliJIdeaUltimate => "The Leading Java and Kotlin IDE",
-            Self::PyCharmCommunity | Self::PyCharmProfessional => "The Python IDE for Professional Developers",
-            Self::CLion => "A cross-platform IDE for C and C++",
-            Self::RustRover => "Focus on what matters",
-            Self::WebStorm => "The JavaScript and TypeScript IDE",
-            Self::PhpStorm => "The Lightning-Smart PHP IDE",
-            Self::Rider => "Fast & powerful cross-platform .NET IDE",
-            Self::GoLand => "The complete IDE crafted for Gophers",
-            Self::RubyMine => "Empowering Ruby Developers",
-            Self::DataGrip => "Many databases, one tool",
-            Self::DataSpell => "Turn data into insights with ease",
-            Self::Fleet => "Next-generation IDE by JetBrains",
-            Self::Aqua => "An IDE for writing tests you can be proud of",
-            Self::Writerside => "Write, test, build, and publish the best documentation",
-            Self::DotMemory => "The .NET Memory Profiler",
-            Self::DotTrace => ".NET Performance Profiler",
-            Self::MPS => "Meta Programming System",
-
-            Self::Space => "The Intelligent Code Collaboration Platform",
-            Self::Gateway => "Your single entry point to all remote development environments",
-        }
+        &self.0.description
+    }
+
+    /// Get the provider of the releases of this tool kind.
+    #[must_use]
+    pub fn provider(&self) -> Provider {
+        self.0.provider()
+    }
+
+    /// Get the release type used for this tool kind when none is specified.
+    #[must_use]
+    pub fn channel(&self) -> Type {
+        self.0.channel
     }
 
     /// Get the linked tool of this kind.
@@ -220,60 +167,64 @@ impl Kind {
 
 impl Display for Kind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
-            Self::IntelliJIdeaUltimate => "IntelliJ IDEA Ultimate",
-            Self::IntelliJIdeaCommunity => "IntelliJ IDEA Community",
-            Self::PyCharmProfessional => "PyCharm Professional",
-            Self::PyCharmCommunity => "PyCharm Community",
-            Self::PhpStorm => "PhpStorm",
-            Self::GoLand => "GoLand",
-            Self::Rider => "Rider",
-            Self::CLion => "CLion",
-            Self::RustRover => "RustRover",
-            Self::WebStorm => "WebStorm",
-            Self::RubyMine => "RubyMine",
-            Self::DataGrip => "DataGrip",
-            Self::DataSpell => "DataSpell",
-            Self::Fleet => "Fleet",
-            Self::Aqua => "Aqua",
-            Self::Writerside => "Writerside",
-            Self::DotMemory => "dotMemory",
-            Self::DotTrace => "dotTrace",
-            Self::MPS => "MPS",
-            Self::Space => "Space",
-            Self::Gateway => "Gateway",
-        })
+        write!(f, "{}", self.0.name)
+    }
+}
+
+impl Debug for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Kind").field(&self.as_str()).finish()
     }
 }
 
 impl FromStr for Kind {
     type Err = anyhow::Error;
 
+    /// Parses a tool kind from any of its names, ignoring case and separators (e.g. "IntelliJ IDEA" or "rr").
+    ///
+    /// Names shared by several kinds (e.g. the "idea" binary) resolve to the first kind in the registry.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "idea-ultimate" => Ok(Self::IntelliJIdeaUltimate),
-            "idea-community" => Ok(Self::IntelliJIdeaCommunity),
-            "pycharm-professional" => Ok(Self::PyCharmProfessional),
-            "pycharm-community" => Ok(Self::PyCharmCommunity),
-            "phpstorm" => Ok(Self::PhpStorm),
-            "goland" => Ok(Self::GoLand),
-            "rider" => Ok(Self::Rider),
-            "clion" => Ok(Self::CLion),
-            "rustrover" => Ok(Self::RustRover),
-            "webstorm" => Ok(Self::WebStorm),
-            "rubymine" => Ok(Self::RubyMine),
-            "datagrip" => Ok(Self::DataGrip),
-            "dataspell" => Ok(Self::DataSpell),
-            "fleet" => Ok(Self::Fleet),
-            "aqua" => Ok(Self::Aqua),
-            "writerside" => Ok(Self::Writerside),
-            "dotmemory" => Ok(Self::DotMemory),
-            "dottrace" => Ok(Self::DotTrace),
-            "mps" => Ok(Self::MPS),
-            "space" => Ok(Self::Space),
-            "gateway" => Ok(Self::Gateway),
-            _ => anyhow::bail!("Unknown tool kind: {}", s),
+        if let Some(kind) = Self::list().iter().find(|kind| kind.as_str() == s) {
+            return Ok(*kind);
+        }
+
+        let name = normalize(s);
+        let rank = |kind: &Self| kind.ranked_names()
+            .filter(|(_, known)| normalize(known) == name)
+            .map(|(rank, _)| rank)
+            .min();
+        // The first kind with the strongest match wins
+        let found = Self::list().iter()
+            .filter_map(|kind| rank(kind).map(|rank| (rank, *kind)))
+            .min_by_key(|(rank, _)| *rank);
+
+        if let Some((_, kind)) = found {
+            return Ok(kind);
+        }
+
+        let suggestions = suggest(&name);
+        if suggestions.is_empty() {
+            anyhow::bail!("Unknown tool kind: {s}");
         }
+
+        let suggestions = suggestions.iter()
+            .map(Self::as_str)
+            .collect::<Vec<_>>();
+        anyhow::bail!("Unknown tool kind: {s} (did you mean {}?)", suggestions.join(", "))
+    }
+}
+
+impl PartialEq for Kind {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Kind {}
+
+impl Hash for Kind {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
     }
 }
 
@@ -296,4 +247,61 @@ impl Serialize for Kind {
     {
         self.as_str().serialize(serializer)
     }
-}
\ No newline at end of file
+}
+
+/// Returns the kinds closest to a normalized name, by edit distance to any of their names.
+///
+/// Only kinds within a third of the name's length are suggested, so that unrelated kinds are never suggested.
+fn suggest(name: &str) -> Vec<Kind> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut suggestions = Kind::list().iter()
+        .filter_map(|kind| {
+            kind.names().iter()
+                .map(|known| distance(name, &normalize(known)))
+                .min()
+                .filter(|distance| *distance <= max_distance)
+                .map(|distance| (distance, *kind))
+        })
+        .collect::<Vec<_>>();
+
+    // The sort is stable, so kinds at the same distance stay in registry order
+    suggestions.sort_by_key(|(distance, _)| *distance);
+    suggestions.into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, kind)| kind)
+        .collect()
+}
+
+/// Returns the edit distance between two strings, where a transposition of two adjacent characters counts as one edit
+/// (the optimal string alignment distance, so that common typos such as "pychram" are close to the right name).
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // distances[i][j] is the distance between the first i characters of a and the first j characters of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distances[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
@@ -0,0 +1,172 @@
+//! Installation metadata
+//!
+//! This module contains the metadata recorded in each tool's directory when it is installed.
+//! A tool's directory name only tells which tool it is, so the metadata records where it came from and when.
+//! Every field but the download key and target is optional, as tools installed by older versions do not have them.
+
+use std::path::Path;
+use std::time::SystemTime;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use crate::api::{Date, Download, Release, Target};
+use crate::tool::{Tool, Type};
+use crate::util::Archive;
+
+/// The metadata of an installed tool.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Metadata {
+    /// The key of the download that was installed (e.g. linuxARM64), or `local` for local archives
+    pub download: String,
+    /// The platform and architecture the tool was built for
+    pub target: Target,
+    /// Where the archive was installed from (e.g. its download URL, or its path for local archives)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The SHA-256 hash of the archive, in hexadecimal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// The size of the archive, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// When the tool was installed, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<u64>,
+    /// The date of the release, if its provider reported one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<Date>,
+    /// The release channel of the tool (e.g. eap)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Type>,
+    /// The version of jb that installed the tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jb_version: Option<String>,
+}
+
+impl Metadata {
+    /// The name of the metadata file, relative to the tool's directory.
+    pub const FILE: &'static str = ".jb-install.json";
+
+    /// Creates the metadata of a tool about to be installed from a download, for a target.
+    ///
+    /// The archive itself is recorded once extracted (see [`Metadata::with_archive`]).
+    #[must_use]
+    pub fn new(download: impl Into<String>, target: Target) -> Self {
+        Self {
+            download: download.into(),
+            target,
+            source: None,
+            sha256: None,
+            size: None,
+            installed_at: None,
+            release_date: None,
+            channel: None,
+            jb_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }
+    }
+
+    /// Creates the metadata of a tool about to be installed from a release, for a target.
+    #[must_use]
+    pub fn from_release(release: &Release, download: &Download, target: Target) -> Self {
+        Self {
+            source: Some(download.link.clone()),
+            release_date: release.date,
+            channel: Some(release.release),
+            ..Self::new(download.key.clone(), target)
+        }
+    }
+
+    /// Records where the archive was installed from.
+    #[must_use]
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Records the release channel of the tool.
+    #[must_use]
+    pub fn with_channel(mut self, channel: Option<Type>) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Records the extracted archive, and the current time as the time of install.
+    #[must_use]
+    pub fn with_archive(mut self, archive: &Archive) -> Self {
+        self.sha256 = Some(archive.sha256.clone());
+        self.size = Some(archive.size);
+        self.installed_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs());
+        self
+    }
+
+    /// Returns the date the tool was installed on, in UTC, if known.
+    #[must_use]
+    pub fn installed_on(&self) -> Option<Date> {
+        self.installed_at
+            .map(|seconds| Date::from_days(i64::try_from(seconds / 86400).unwrap_or(i64::MAX)))
+    }
+
+    /// Reads the metadata of an installed tool.
+    ///
+    /// This returns `None` if the tool has no metadata, for example if it was installed by an older version.
+    ///
+    /// # Errors
+    /// This function will return an error if the metadata file exists but cannot be read or parsed.
+    pub fn read(tool: &Tool) -> anyhow::Result<Option<Self>> {
+        let path = tool.as_path().join(Self::FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let metadata = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        Ok(Some(metadata))
+    }
+
+    /// Writes the metadata of an installed tool.
+    ///
+    /// # Errors
+    /// This function will return an error if the metadata file cannot be written.
+    pub fn write(&self, tool: &Tool) -> anyhow::Result<()> {
+        self.write_to(&tool.as_path())
+    }
+
+    /// Writes the metadata to a tool's directory, which may not be installed yet (e.g. while staging).
+    ///
+    /// # Errors
+    /// This function will return an error if the metadata file cannot be written.
+    pub fn write_to(&self, folder: &Path) -> anyhow::Result<()> {
+        let path = folder.join(Self::FILE);
+
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl Tool {
+    /// Returns the metadata of the tool, if it is installed and has any.
+    ///
+    /// Invalid metadata is ignored (logged as debug).
+    #[must_use]
+    pub fn metadata(&self) -> Option<Metadata> {
+        Metadata::read(self).unwrap_or_else(|err| {
+            crate::debug!("Ignoring metadata of {}: {err:#}", self.as_str());
+            None
+        })
+    }
+
+    /// Returns whether the tool was built for the current host.
+    ///
+    /// Tools without metadata are assumed to be built for the current host.
+    #[must_use]
+    pub fn is_native(&self) -> bool {
+        self.metadata()
+            .map_or(true, |metadata| metadata.target.is_host())
+    }
+}
@@ -0,0 +1,366 @@
+//! Product registry
+//!
+//! This module contains the registry of products that can be installed, which every [`Kind`] points into.
+//! The registry is built from a table shipped with the CLI, then extended with the products fetched from `JetBrains`'
+//! API (see [`refresh`]), and finally with the products defined in the local registry file, which can also override
+//! built-in products.
+//! Local products that are not in the releases API are downloaded from their own URL template instead.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use anyhow::Context;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::api::{Provider, Target};
+use crate::env::Variable;
+use crate::tool::{Build, Kind, Type, Version};
+
+/// The built-in product table.
+const BUILTIN: &str = include_str!("products.json");
+
+/// The registry, loaded once on first use.
+static REGISTRY: Lazy<Vec<Kind>> = Lazy::new(load);
+
+/// A product that can be installed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Product {
+    /// The slug of the product, used on the command line and for install directories (e.g. idea-ultimate)
+    pub slug: String,
+    /// The code of the product in the releases API (e.g. IIU), empty for products with a download template
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    /// The product code found in `product-info.json` (e.g. IU), for products based on the `IntelliJ` platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_code: Option<String>,
+    /// The display name of the product (e.g. IntelliJ IDEA Ultimate)
+    pub name: String,
+    /// Other names accepted on the command line, besides the slug, codes, binary and display name (e.g. intellij)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// A short description of the product, used in desktop entries
+    #[serde(default)]
+    pub description: String,
+    /// The name of the binary linked to the PATH (e.g. idea)
+    pub binary: String,
+    /// The path to the executable, relative to the tool's directory (e.g. bin/idea.sh)
+    pub executable: String,
+    /// The path to the icon, relative to the tool's directory (e.g. bin/idea.svg)
+    pub icon: String,
+    /// The release channel used when none is specified
+    #[serde(default = "default_channel")]
+    pub channel: Type,
+    /// Where the releases of the product are fetched from (e.g. data-services or android-studio)
+    #[serde(default, skip_serializing_if = "Provider::is_default")]
+    pub provider: Provider,
+
+    /// A template of the download URL, for products that are not in the releases API
+    /// (e.g. `https://example.com/ide-{version}-{platform}-{arch}.tar.gz`, see [`Product::render`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download: Option<String>,
+    /// A template of the checksum URL, for products with a download template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// The version installed when none is specified, for products with a download template (e.g. 2024.1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The build installed when none is specified, for products with a download template (e.g. 241.1),
+    /// which is required along with a default version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+}
+
+impl Product {
+    /// Checks that the product can be used as a tool kind.
+    ///
+    /// # Errors
+    /// This function will return an error if the slug is empty or cannot be used in a tool's directory name,
+    /// if the product has neither a code nor a download template, if its executable or icon is not inside the tool's
+    /// directory, or if its default version or build is invalid or missing.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.slug.is_empty() {
+            anyhow::bail!("Product {} must have a slug", self.name);
+        }
+        if self.code.is_empty() && self.provider() != Provider::Template {
+            anyhow::bail!("Product {} must have either a code or a download template", self.slug);
+        }
+
+        // The slug is the first part of a tool's directory name, which uses underscores as separators
+        if self.slug.starts_with('.') || !self.slug.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.')) {
+            anyhow::bail!("Invalid product slug {} (only letters, digits, dashes and dots are allowed)", self.slug);
+        }
+
+        // The executable and icon are linked from outside the tool's directory, so they must stay inside it
+        for (field, path) in [("executable", &self.executable), ("icon", &self.icon)] {
+            let path = Path::new(path);
+            if path.is_absolute() || path.components().any(|component| component == Component::ParentDir) {
+                anyhow::bail!("Invalid {field} {} for product {} (must be relative to the tool's directory)", path.display(), self.slug);
+            }
+        }
+
+        if self.is_templated() && self.version.is_some() && self.build.is_none() {
+            anyhow::bail!("Product {} must have a default build along with its default version", self.slug);
+        }
+
+        if let Some(version) = &self.version {
+            Version::from_str(version)
+                .with_context(|| format!("Invalid version {version} for product {}", self.slug))?;
+        }
+        if let Some(build) = &self.build {
+            Build::from_str(build)
+                .with_context(|| format!("Invalid build {build} for product {}", self.slug))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the product is downloaded from its own template, instead of a release feed.
+    #[must_use]
+    pub fn is_templated(&self) -> bool {
+        self.download.is_some()
+    }
+
+    /// Returns the provider of the product's releases.
+    ///
+    /// Products with a download template always use it, whatever their provider.
+    #[must_use]
+    pub fn provider(&self) -> Provider {
+        if self.is_templated() {
+            Provider::Template
+        } else {
+            self.provider
+        }
+    }
+
+    /// Renders a download or checksum URL template for a version, build and target.
+    ///
+    /// The placeholders `{version}`, `{build}`, `{platform}` (e.g. linux) and `{arch}` (e.g. `x86_64`) are replaced.
+    #[must_use]
+    pub fn render(template: &str, version: &Version, build: &Build, target: &Target) -> String {
+        template
+            .replace("{version}", &version.to_string())
+            .replace("{build}", &build.to_string())
+            .replace("{platform}", &target.platform)
+            .replace("{arch}", &target.arch)
+    }
+}
+
+fn default_channel() -> Type {
+    Type::Release
+}
+
+/// Normalizes a product name, so that names can be compared regardless of case and separators
+/// (e.g. `IntelliJ IDEA` becomes `intellij-idea`).
+#[must_use]
+pub(crate) fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Returns every product kind in the registry, in display order.
+#[must_use]
+pub fn registry() -> &'static [Kind] {
+    &REGISTRY
+}
+
+/// Returns the built-in products.
+///
+/// # Panics
+/// If the built-in product table is invalid.
+#[must_use]
+pub fn builtin() -> Vec<Product> {
+    serde_json::from_str(BUILTIN).expect("Built-in product table is invalid")
+}
+
+/// Returns the path where the products fetched from the API are stored.
+#[must_use]
+pub fn refreshed_path() -> PathBuf {
+    Variable::CacheDirectory.get::<PathBuf>()
+        .join("products.json")
+}
+
+/// Returns the path of the local product registry.
+#[must_use]
+pub fn local_path() -> PathBuf {
+    Variable::ProductsFile.get::<PathBuf>()
+}
+
+/// Fetches the products from `JetBrains`' API, and stores them so that the registry includes them from now on.
+///
+/// Only products based on the `IntelliJ` platform and available for Linux are kept, as their layout is known.
+/// Returns the fetched products.
+///
+/// # Errors
+/// This function will return an error if running offline, if the request fails, or if the products cannot be stored.
+pub fn refresh() -> anyhow::Result<Vec<Product>> {
+    if Variable::Offline.get_bool() {
+        anyhow::bail!("Cannot refresh products while offline");
+    }
+
+    let mut url = crate::api::endpoint("products")?;
+    url.query_pairs_mut()
+        .append_pair("fields", "code,name,description,intellijProductCode,distributions");
+
+    let client = crate::http::blocking()?;
+    let remote = crate::http::retry(|| {
+        client.get(url.clone())
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .with_context(|| format!("Failed to fetch {url}"))?
+            .json::<Vec<Remote>>()
+            .with_context(|| format!("Failed to parse {url}"))
+    })?;
+
+    let products = remote.into_iter()
+        .filter_map(Remote::into_product)
+        .collect::<Vec<_>>();
+
+    let path = refreshed_path();
+    let directory = path.parent().unwrap();
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+    // Write to a temporary file first, so that concurrent readers never see a partial registry
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temp, serde_json::to_string_pretty(&products)?)
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, &path)
+        .with_context(|| format!("Failed to move {} to {}", temp.display(), path.display()))?;
+
+    crate::debug!("Stored {} products to {}", products.len(), path.display());
+
+    Ok(products)
+}
+
+/// A product, as returned by the products endpoint of `JetBrains`' API.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Remote {
+    code: String,
+    name: String,
+    description: Option<String>,
+    intellij_product_code: Option<String>,
+    #[serde(default)]
+    distributions: HashMap<String, serde_json::Value>,
+}
+
+impl Remote {
+    /// Converts the product, guessing its slug and layout from its name as `IntelliJ`-based products share the same one.
+    fn into_product(self) -> Option<Product> {
+        let product_code = self.intellij_product_code?;
+        if !self.distributions.contains_key("linux") {
+            return None;
+        }
+
+        let slug = normalize(&self.name);
+        let binary = slug.replace('-', "");
+
+        let product = Product {
+            executable: format!("bin/{binary}.sh"),
+            icon: format!("bin/{binary}.svg"),
+            slug,
+            code: self.code,
+            product_code: Some(product_code),
+            name: self.name,
+            aliases: Vec::new(),
+            description: self.description.unwrap_or_default(),
+            binary,
+            channel: Type::Release,
+            provider: Provider::DataServices,
+            download: None,
+            checksum: None,
+            version: None,
+            build: None,
+        };
+
+        product.validate().ok()?;
+        Some(product)
+    }
+}
+
+/// Builds the registry from the built-in, refreshed and local products.
+fn load() -> Vec<Kind> {
+    let mut products = builtin();
+
+    // Fetched products never replace built-in ones, whose layout is known to be right
+    for product in read(&refreshed_path()) {
+        if !products.iter().any(|known| known.code == product.code || known.slug == product.slug) {
+            products.push(product);
+        }
+    }
+
+    // Local products replace the ones with the same slug
+    for product in read(&local_path()) {
+        match products.iter_mut().find(|known| known.slug == product.slug) {
+            Some(known) => *known = product,
+            None => products.push(product),
+        }
+    }
+
+    products.into_iter()
+        .map(|product| Kind::new(Box::leak(Box::new(product))))
+        .collect()
+}
+
+/// Reads a product registry file, skipping (with a warning) invalid products or an invalid file.
+fn read(path: &Path) -> Vec<Product> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            crate::warn!("Failed to read {}: {err}", path.display());
+            return Vec::new();
+        }
+    };
+
+    let products = match serde_json::from_str::<Vec<Product>>(&content) {
+        Ok(products) => products,
+        Err(err) => {
+            crate::warn!("Failed to parse {}: {err}", path.display());
+            return Vec::new();
+        }
+    };
+
+    products.into_iter()
+        .filter(|product| match product.validate() {
+            Ok(()) => true,
+            Err(err) => {
+                crate::warn!("Ignoring a product from {}: {err:#}", path.display());
+                false
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(executable: &str, icon: &str) -> Product {
+        serde_json::from_value(serde_json::json!({
+            "slug": "idea-ultimate",
+            "code": "IIU",
+            "name": "IntelliJ IDEA Ultimate",
+            "binary": "idea",
+            "executable": executable,
+            "icon": icon,
+        })).unwrap()
+    }
+
+    #[test]
+    fn validate_accepts_relative_paths() {
+        assert!(product("bin/idea.sh", "bin/idea.svg").validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_paths_outside_the_tool() {
+        assert!(product("/usr/bin/sh", "bin/idea.svg").validate().is_err());
+        assert!(product("bin/../../idea.sh", "bin/idea.svg").validate().is_err());
+        assert!(product("bin/idea.sh", "/etc/passwd").validate().is_err());
+        assert!(product("bin/idea.sh", "../idea.svg").validate().is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Product information
+//!
+//! This module contains the `product-info.json` file shipped at the root of every `IntelliJ`-based tool,
+//! which describes the product, its version and how to launch it.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Component, Path};
+use std::str::FromStr;
+use anyhow::Context;
+use serde::Deserialize;
+use crate::api::Target;
+use crate::archive::Format;
+use crate::tool::{Build, Kind, Tool, Type, Version};
+
+/// The product information of a tool.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductInfo {
+    /// The name of the product (e.g. IntelliJ IDEA)
+    pub name: String,
+    /// The version of the product (e.g. 2024.1)
+    pub version: String,
+    /// The build number of the product (e.g. 241.14494.240)
+    pub build_number: String,
+    /// The product code (e.g. IU), which may differ from the one used by the API
+    pub product_code: String,
+    /// The vendor of the product (e.g. `JetBrains`, Google), which names the parent of the configuration directory
+    pub product_vendor: Option<String>,
+    /// The name of the configuration and system directories (e.g. IntelliJIdea2024.1)
+    pub data_directory_name: Option<String>,
+    /// The suffix of the version, used for pre-releases (e.g. EAP)
+    pub version_suffix: Option<String>,
+    /// The launchers of the product, for each platform and architecture
+    #[serde(default)]
+    pub launch: Vec<Launch>,
+}
+
+/// A launcher of a tool, as described in its product information.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Launch {
+    /// The platform of the launcher (e.g. Linux)
+    pub os: Option<String>,
+    /// The architecture of the launcher (e.g. amd64)
+    pub arch: Option<String>,
+    /// The path to the launcher, relative to the tool's directory (e.g. bin/idea.sh)
+    pub launcher_path: Option<String>,
+}
+
+impl ProductInfo {
+    /// The name of the product information file, relative to the tool's directory.
+    pub const FILE: &'static str = "product-info.json";
+
+    /// Reads the product information of an extracted tool.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be read or parsed.
+    pub fn read(folder: &Path) -> anyhow::Result<Self> {
+        let path = folder.join(Self::FILE);
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Reads the product information from an archive, without extracting it.
+    ///
+    /// The file is looked up at the root of the archive, or in its top-level directory.
+    ///
+    /// # Errors
+    /// This function will return an error if the archive cannot be read, or if it has no valid product information.
+    pub fn from_archive(archive: &Path) -> anyhow::Result<Self> {
+        let format = Format::detect(archive, None)?;
+        let file = File::open(archive)
+            .with_context(|| format!("Failed to open {}", archive.display()))?;
+
+        let content = if format.is_tar() {
+            Self::find_in_tar(format.decoder(BufReader::new(file))?)
+        } else {
+            Self::find_in_zip(BufReader::new(file))
+        }.with_context(|| format!("Failed to read {}", archive.display()))?;
+
+        let Some(content) = content else {
+            anyhow::bail!("No {} found in {}", Self::FILE, archive.display());
+        };
+
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {} in {}", Self::FILE, archive.display()))
+    }
+
+    /// Returns the tool kind of the product.
+    ///
+    /// # Errors
+    /// This function will return an error if the product code is not a known tool kind.
+    pub fn kind(&self) -> anyhow::Result<Kind> {
+        Kind::from_product_code(&self.product_code)
+            .ok_or_else(|| anyhow::anyhow!("Unknown product code {} ({})", self.product_code, self.name))
+    }
+
+    /// Returns the tool described by the product information.
+    ///
+    /// Products with a version suffix (e.g. EAP) are considered pre-releases.
+    ///
+    /// # Errors
+    /// This function will return an error if the product code, version or build number is not valid.
+    pub fn tool(&self) -> anyhow::Result<Tool> {
+        let kind = self.kind()?;
+        let version = Version::from_str(&self.version)
+            .with_context(|| format!("Invalid version {}", self.version))?;
+        let build = Build::from_str(&self.build_number)
+            .with_context(|| format!("Invalid build number {}", self.build_number))?;
+
+        let suffix = self.version_suffix.as_deref().unwrap_or_default().to_lowercase();
+        let release = if suffix.is_empty() {
+            Type::Release
+        } else if suffix.contains("preview") {
+            Type::Preview
+        } else {
+            Type::EAP
+        };
+
+        Ok(Tool::new(kind, Some(version), Some(build), Some(release)))
+    }
+
+    /// Returns the target of the product, from its first launcher.
+    ///
+    /// Older products do not describe their launchers, in which case this returns `None`.
+    #[must_use]
+    pub fn target(&self) -> Option<Target> {
+        let launch = self.launch.first()?;
+        Target::new(launch.os.as_deref()?, launch.arch.as_deref()?).ok()
+    }
+
+    fn find_in_tar(reader: impl Read) -> anyhow::Result<Option<String>> {
+        let mut tar = tar::Archive::new(reader);
+
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            if !Self::is_file(&entry.path()?) {
+                continue;
+            }
+
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            return Ok(Some(content));
+        }
+
+        Ok(None)
+    }
+
+    fn find_in_zip(reader: impl Read + Seek) -> anyhow::Result<Option<String>> {
+        let mut zip = zip::ZipArchive::new(reader)?;
+
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index)?;
+            if !entry.enclosed_name().is_some_and(|path| Self::is_file(&path)) {
+                continue;
+            }
+
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            return Ok(Some(content));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns whether a path in an archive is the product information file, at its root or in its top-level directory.
+    fn is_file(path: &Path) -> bool {
+        let components = path.components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect::<Vec<_>>();
+
+        match components.as_slice() {
+            [file] | [_, file] => file.as_os_str() == Self::FILE,
+            _ => false,
+        }
+    }
+}
@@ -14,13 +14,10 @@ impl Type {
     /// Returns the default release type for the given kind.
     ///
     /// This is used to determine the default release type for a tool kind, when the release type is not specified.
+    /// It is set by the product registry (see [`super::Product::channel`]).
     #[must_use]
     pub fn kind_default(kind: Kind) -> Self {
-        match kind {
-            Kind::Fleet | Kind::Aqua => Self::Preview,
-            Kind::Writerside | Kind::RustRover => Self::EAP,
-            _ => Self::Release,
-        }
+        kind.channel()
     }
 
     /// Returns the release type as a string.
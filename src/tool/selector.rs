@@ -0,0 +1,117 @@
+//! Tool selectors
+//!
+//! This module contains the selectors given to commands to pick a tool, such as `idea-ultimate@latest`,
+//! `rustrover@eap`, `goland@2024.1` or `clion@linked`.
+//! The underscore format of tool directories (e.g. `idea-ultimate_2024.1-241.14494-eap`) is still accepted.
+//! Every selector is turned into a [`Tool`] by [`Selector::resolve`], so that all commands resolve them the same way.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use anyhow::Context;
+use crate::tool::{Constraint, Kind, List, Tool, Type};
+
+/// A selector of a tool, as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Selector {
+    pub kind: Kind,
+    pub spec: Spec,
+}
+
+/// What a selector picks among the tools of its kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Spec {
+    /// The tool, in the underscore format of tool directories (e.g. `idea-ultimate_2024.1-241.14494-eap`)
+    Tool(Tool),
+    /// The latest tool (e.g. `idea-ultimate@latest`)
+    Latest,
+    /// The tool linked to the PATH (e.g. `clion@linked`)
+    Linked,
+    /// The latest tool of a release channel (e.g. `rustrover@eap`)
+    Channel(Type),
+    /// The latest tool matching a version or build constraint (e.g. `goland@2024.1` or `goland@>=2024.1,<2024.3`)
+    Constraint(Constraint),
+}
+
+/// Where a selector looks for tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The releases that can be installed (e.g. for install or notes)
+    Available,
+    /// The tools that are already installed (e.g. for link or uninstall)
+    Installed,
+}
+
+impl Selector {
+    /// Resolves the selector to a tool.
+    ///
+    /// When looking for releases, selectors on a version or channel resolve to a tool matching them, which commands then
+    /// match against releases as usual. When looking for installed tools, they resolve to the newest installed tool
+    /// matching them, the same way selectors on the latest tool do, so that a single tool is ever picked.
+    /// Selectors on the linked tool always resolve to the installed one.
+    ///
+    /// # Errors
+    /// This function will return an error if no tool is linked or installed when the selector requires one,
+    /// or if the installed tools cannot be listed.
+    pub fn resolve(&self, scope: Scope) -> anyhow::Result<Tool> {
+        let kind = self.kind;
+
+        let pattern = match &self.spec {
+            Spec::Tool(tool) => return Ok(tool.clone()),
+            Spec::Linked => return kind.linked()
+                .with_context(|| format!("Failed to list the installed tools of {kind}"))?
+                .with_context(|| format!("No linked tool found for {kind}")),
+            Spec::Latest => Tool::from_kind(kind),
+            Spec::Channel(release) => Tool::new(kind, None, None, Some(*release)),
+            Spec::Constraint(constraint) => Tool::from_kind(kind).with_constraint(Some(constraint.clone())),
+        };
+
+        match scope {
+            Scope::Available => Ok(pattern),
+            Scope::Installed => pattern.list_matching()
+                .with_context(|| format!("Failed to list the installed tools of {kind}"))?
+                .into_iter()
+                .max()
+                .with_context(|| format!("No installed tool found for {self}")),
+        }
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.spec {
+            Spec::Tool(tool) => write!(f, "{}", tool.as_str()),
+            Spec::Latest => write!(f, "{}@latest", self.kind.as_str()),
+            Spec::Linked => write!(f, "{}@linked", self.kind.as_str()),
+            Spec::Channel(release) => write!(f, "{}@{}", self.kind.as_str(), release.as_str()),
+            Spec::Constraint(constraint) => write!(f, "{}@{constraint}", self.kind.as_str()),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((kind, spec)) = s.split_once('@') else {
+            let tool = Tool::from_str(s)?;
+            return Ok(Self { kind: tool.kind, spec: Spec::Tool(tool) });
+        };
+
+        // The error already names the kind, and may suggest other kinds
+        let kind = kind.parse::<Kind>()?;
+
+        let spec = match spec.trim().to_lowercase().as_str() {
+            "" => anyhow::bail!("Empty selector for {kind} (e.g. {}@latest)", kind.as_str()),
+            "latest" => Spec::Latest,
+            "linked" => Spec::Linked,
+            spec => match Type::from_str(spec) {
+                Ok(release) => Spec::Channel(release),
+                Err(_) => Spec::Constraint(spec.parse::<Constraint>().map_err(|err| anyhow::anyhow!(
+                    "Invalid selector {s}, expected latest, linked, a release type or a version ({err:#})"
+                ))?),
+            },
+        };
+
+        Ok(Self { kind, spec })
+    }
+}
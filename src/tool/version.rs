@@ -1,24 +1,30 @@
 //! Version types and parsing
 //!
 //! This module contains types and parsing for version numbers.
+//! A version starts with a major version (e.g. 2024.1), followed by any number of components (e.g. 2024.1.1.12)
+//! and an optional qualifier (e.g. 2024.2.EAP).
+//! Qualifiers are separated by dots, so that versions can be used in the tools' directory names.
 
+use std::cmp::Ordering;
 use std::fmt::Display;
 use std::str::FromStr;
 use anyhow::Context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// A version number
-#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[readonly::make]
 pub struct Version {
     /// The major version (e.g. 2021.1)
     pub major: Major,
-    /// The minor version (if any)
-    pub minor: Option<u8>,
+    /// The components after the major version, if any (e.g. 2 and 1 in 2024.1.2.1)
+    pub components: Vec<u32>,
+    /// The qualifier of the version, used for pre-releases (e.g. EAP)
+    pub qualifier: Option<String>,
 }
 
 /// A major version number (e.g. 2021.1)
-#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[readonly::make]
 pub struct Major {
     pub year: u16,
@@ -26,36 +32,73 @@ pub struct Major {
 }
 
 impl Version {
+    /// Creates a version, normalizing its qualifier (see [`normalize_qualifier`]).
     #[must_use]
-    pub fn new(major: Major, minor: Option<u8>) -> Self {
-        Self { major, minor }
+    pub fn new(major: Major, components: Vec<u32>, qualifier: Option<String>) -> Self {
+        Self { major, components, qualifier: normalize_qualifier(qualifier) }
+    }
+
+    /// Returns the minor version (e.g. 2 in 2024.1.2), if any.
+    #[must_use]
+    pub fn minor(&self) -> Option<u32> {
+        self.components.first().copied()
+    }
+
+    /// Returns every numeric component of the version, including the major version (e.g. 2024, 1 and 2 in 2024.1.2).
+    #[must_use]
+    pub fn parts(&self) -> Vec<u32> {
+        [u32::from(self.major.year), u32::from(self.major.month)]
+            .into_iter()
+            .chain(self.components.iter().copied())
+            .collect()
     }
 
     /// Returns whether the version matches another version.
     ///
     /// This is used to check if a version matches another version, used in arguments and commands.
-    ///
-    /// # Panics
-    /// This function will panic if the versions are not valid.
+    /// The other version matches if it starts with the same components (e.g. 2024.1 matches 2024.1.2),
+    /// and has the same qualifier if this version has one.
     #[must_use]
     pub fn matched(&self, other: &Version) -> bool {
         if self.major != other.major {
             return false;
         }
 
-        if let Some(minor) = self.minor {
-            if other.minor.is_none() || minor != other.minor.unwrap() {
-                return false;
-            }
+        if !other.components.starts_with(&self.components) {
+            return false;
         }
 
-        true
+        self.qualifier.is_none() || qualifier_eq(self.qualifier.as_deref(), other.qualifier.as_deref())
+    }
+}
+
+impl Ord for Version {
+    /// Versions are ordered by their components, and a qualified version comes before the same unqualified version
+    /// (e.g. 2024.2.EAP is before 2024.2).
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.major.cmp(&other.major)
+            .then_with(|| self.components.cmp(&other.components))
+            .then_with(|| compare_qualifiers(self.qualifier.as_deref(), other.qualifier.as_deref()))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}", self.major, self.minor.map_or(String::new(), |m| format!(".{m}")))
+        write!(f, "{}", self.major)?;
+        for component in &self.components {
+            write!(f, ".{component}")?;
+        }
+        if let Some(qualifier) = &self.qualifier {
+            write!(f, ".{qualifier}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -63,29 +106,28 @@ impl FromStr for Version {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // If it only has 2 parts, only the major version is present
-        let parts = s.split('.').collect::<Vec<_>>();
+        let (parts, qualifier) = parse_components(s)
+            .with_context(|| format!("Invalid version: {s}"))?;
 
-        if parts.len() < 2 || parts.len() > 3 {
-            anyhow::bail!("Invalid version: {}", s);
-        }
+        let [year, month, components @ ..] = parts.as_slice() else {
+            anyhow::bail!("Invalid version: {s} (expected at least a year and a month, e.g. 2024.1)");
+        };
 
-        let major = Major::from_str(format!("{}.{}", parts[0], parts[1]).as_str())
-            .with_context(|| format!("Failed to parse major version: {}", parts[..1].join(".")))?;
-
-        if parts.len() == 2 {
-            return Ok(Self::new(major, None));
-        }
-
-        // If it has 3 parts, both the major and minor version are present
-        let minor = parts[2].parse::<u8>()
-            .with_context(|| format!("Failed to parse minor version: {}", parts[2]));
+        let year = u16::try_from(*year)
+            .with_context(|| format!("Failed to parse year: {year}"))?;
+        let month = u8::try_from(*month)
+            .with_context(|| format!("Failed to parse month: {month}"))?;
 
-        if minor.is_err() {
-            return Ok(Self::new(major, None));
-        }
+        Ok(Self::new(Major::new(year, month), components.to_vec(), qualifier))
+    }
+}
 
-        Ok(Self::new(major, Some(minor.unwrap())))
+impl Serialize for Version {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
     }
 }
 
@@ -132,3 +174,138 @@ impl FromStr for Major {
         Ok(Self::new(year, month))
     }
 }
+
+impl Serialize for Major {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Major {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Splits a version or build number into its numeric components and its qualifier (e.g. `2024.2.EAP` or `242.SNAPSHOT`).
+///
+/// The qualifier starts at the first character that is neither a digit nor a dot,
+/// and may be separated from the components by a dot, a dash or a space.
+pub(crate) fn parse_components(s: &str) -> anyhow::Result<(Vec<u32>, Option<String>)> {
+    let s = s.trim();
+    let (numbers, qualifier) = match s.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(index) => {
+            let qualifier = s[index..].trim_start_matches(['.', '-', ' ']);
+            if qualifier.is_empty() {
+                anyhow::bail!("Empty qualifier");
+            }
+            (s[..index].trim_end_matches('.'), Some(qualifier.to_string()))
+        }
+        None => (s, None),
+    };
+
+    if numbers.is_empty() {
+        anyhow::bail!("No numeric component found");
+    }
+
+    let components = numbers.split('.')
+        .map(|part| part.parse::<u32>().with_context(|| format!("Failed to parse component: {part:?}")))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok((components, qualifier))
+}
+
+/// Normalizes a qualifier so that it is safe in paths and in tools' directory names, by replacing anything but
+/// letters and digits with dots (e.g. `Canary 5` becomes `Canary.5`). An empty qualifier is no qualifier.
+pub(crate) fn normalize_qualifier(qualifier: Option<String>) -> Option<String> {
+    qualifier
+        .map(|qualifier| {
+            qualifier.split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+        .filter(|qualifier| !qualifier.is_empty())
+}
+
+/// Compares qualifiers, where no qualifier comes after any qualifier (as qualifiers mark pre-releases).
+///
+/// Qualifiers are compared segment by segment (e.g. `EAP.9` is before `EAP.10`),
+/// numeric segments as numbers and before any other segment, and the other segments ignoring case.
+pub(crate) fn compare_qualifiers(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let (mut left, mut right) = (a.split('.'), b.split('.'));
+            loop {
+                match (left.next(), right.next()) {
+                    (None, None) => return a.cmp(b),
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(left), Some(right)) => match compare_segments(left, right) {
+                        Ordering::Equal => {}
+                        ordering => return ordering,
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Compares segments of qualifiers, numbers as numbers and before any other segment, and the others ignoring case.
+fn compare_segments(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Returns whether qualifiers are the same, ignoring case.
+pub(crate) fn qualifier_eq(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eap_qualifiers_are_ordered_by_number() {
+        let nine = "2024.2.EAP.9".parse::<Version>().unwrap();
+        let ten = "2024.2.EAP.10".parse::<Version>().unwrap();
+        let release = "2024.2".parse::<Version>().unwrap();
+
+        assert!(nine < ten);
+        assert!(ten < release);
+    }
+
+    #[test]
+    fn canary_qualifiers_are_ordered_by_number() {
+        let nine = "2024.1.1 Canary 9".parse::<Version>().unwrap();
+        let ten = "2024.1.1 Canary 10".parse::<Version>().unwrap();
+
+        assert_eq!(ten.to_string(), "2024.1.1.Canary.10");
+        assert!(nine < ten);
+    }
+
+    #[test]
+    fn qualifiers_ignore_case() {
+        assert_eq!(compare_qualifiers(Some("eap.2"), Some("EAP.10")), Ordering::Less);
+        assert_eq!(compare_qualifiers(Some("EAP"), Some("EAP.1")), Ordering::Less);
+        assert_eq!(compare_qualifiers(Some("1"), Some("EAP")), Ordering::Less);
+    }
+}
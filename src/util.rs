@@ -1,34 +1,111 @@
 //! Module for utilities.
 
 use std::cmp::min;
-use std::io::{self, Read};
-use std::path::PathBuf;
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, BufReader, Read};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
-use flate2::read::GzDecoder;
 use futures_lite::StreamExt;
 use notify_rust::{Hint, Notification, Timeout};
-use tar::Archive;
-use sha2::{Digest, Sha256};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256, Sha512};
+use tokio::io::AsyncWriteExt;
+use crate::archive::Format;
+use crate::env::Variable;
 
-/// Download and extract a tarball from a URL.
+/// The expected checksum of a download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum<'a> {
+    /// A link to a file starting with the SHA-256 or SHA-512 checksum (e.g. `ideaIU-2024.1.tar.gz.sha256`)
+    Link(&'a str),
+    /// The SHA-256 checksum itself, in hexadecimal
+    Sha256(&'a str),
+}
+
+/// An archive that was extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archive {
+    /// The SHA-256 hash of the archive, in hexadecimal
+    pub sha256: String,
+    /// The size of the archive, in bytes
+    pub size: u64,
+}
+
+/// Download and extract an archive from a URL.
 ///
-/// It will directly stream the download to the extraction, so it will not store the tarball on disk.
+/// The archive is first downloaded to the staging area (see [`download`]), so that an interrupted download
+/// can be resumed later on, and is only removed once it has been extracted and verified.
+/// If a checksum is given and the archive cache is enabled, the archive is taken from the cache when possible,
+/// and stored in it once verified.
+/// The detached signature of the archive is checked before extraction, according to the signature policy (see [`crate::verify`]).
 ///
-/// # Errors
-/// This function will return an error if the download or extraction fails.
+/// Returns the hash and size of the extracted archive.
 ///
-/// # Panics
-/// This function will panic if the folder cannot be created or written to.
+/// # Errors
+/// This function will return an error if the download, extraction, checksum or signature verification fails.
 pub fn download_extract(
     url: &str,
     folder: &PathBuf,
-    checksum_url: Option<&str>,
+    checksum: Option<Checksum<'_>>,
+    size: Option<u64>,
     progress: Option<&indicatif::ProgressBar>,
-) -> anyhow::Result<()> {
-    let filename = url.split('/').last().expect("Failed to get filename");
-    if !filename.ends_with(".tar.gz") {
-        anyhow::bail!("Invalid file type: {}", filename);
+) -> anyhow::Result<Archive> {
+    let filename = filename(url)?;
+    if Format::from_name(filename).is_none() {
+        crate::debug!("Unknown archive extension for {filename}, its format will be detected from its content");
+    }
+
+    let checksum = checksum
+        .map(|checksum| match checksum {
+            Checksum::Link(url) => fetch_checksum(url).map(|checksum| checksum.to_lowercase()),
+            Checksum::Sha256(checksum) => Ok(checksum.trim().to_lowercase()),
+        })
+        .transpose()?;
+
+    // SHA-512 checksums (e.g. of runtimes) are checked before extraction, which only computes the SHA-256 hash
+    let (checksum, sha512) = match checksum {
+        Some(checksum) if checksum.len() == 128 => (None, Some(checksum)),
+        checksum => (checksum, None),
+    };
+
+    let cached = checksum
+        .as_deref()
+        .and_then(crate::archive::cache::get);
+
+    let archive = if let Some(cached) = &cached {
+        if let (Some(pb), Ok(metadata)) = (progress, std::fs::metadata(cached)) {
+            pb.set_length(metadata.len());
+            pb.set_position(metadata.len());
+        }
+        cached.clone()
+    } else {
+        download(url, size, progress)?
+    };
+
+    if let Err(err) = crate::verify::verify(&archive, url) {
+        if cached.is_none() {
+            std::fs::remove_file(&archive)
+                .with_context(|| format!("Failed to remove {}", archive.display()))?;
+        }
+        return Err(err);
+    }
+
+    if let Some(expected) = &sha512 {
+        let file = File::open(&archive)
+            .with_context(|| format!("Failed to open {}", archive.display()))?;
+        let mut hasher = Sha512::new();
+        io::copy(&mut BufReader::new(file), &mut hasher)
+            .with_context(|| format!("Failed to read {}", archive.display()))?;
+
+        let actual = format!("{:x}", hasher.finalize());
+        if &actual != expected {
+            std::fs::remove_file(&archive)
+                .with_context(|| format!("Failed to remove {}", archive.display()))?;
+            anyhow::bail!("Checksum mismatch: expected {expected}, got {actual}");
+        }
     }
 
     if !folder.exists() {
@@ -36,100 +113,332 @@ pub fn download_extract(
             .with_context(|| format!("Failed to create {}", folder.display()))?;
     }
 
-    tokio::task::block_in_place(|| {
-        tokio::runtime::Runtime::new()
-            .unwrap()
-            .block_on(async move {
-                let client = reqwest::Client::new();
+    let size = std::fs::metadata(&archive)
+        .with_context(|| format!("Failed to read {}", archive.display()))?
+        .len();
+    let hash = extract(&archive, folder);
+    let verified = match (&hash, &checksum) {
+        (Ok(hash), Some(checksum)) => hash == checksum,
+        (Ok(_), None) => true,
+        (Err(_), _) => false,
+    };
 
-                let response = client.get(url)
-                    .send()
-                    .await
-                    .with_context(|| format!("Failed to fetch {url}"))?;
-
-                let (tx, rx) = flume::bounded(0);
-
-                let folder = folder.clone();
-                let decoder_thread = std::thread::spawn(move || {
-                    let input = ChannelRead::new(rx);
-                    let gz = GzDecoder::new(input);
-                    let mut archive = Archive::new(gz);
-
-                    archive.unpack(&folder)
-                        .with_context(|| format!("Failed to extract to {}", folder.display()))?;
-
-                    strip_content(&folder)
-                        .with_context(|| format!("Failed to strip content of {}", folder.display()))?;
-
-                    let channel = archive.into_inner().into_inner();
-                    Ok::<String, anyhow::Error>(channel.hash())
-                });
-
-                if response.status() == reqwest::StatusCode::OK {
-                    let content_length = response.content_length().unwrap_or(0);
-                    let mut downloaded = 0;
-
-                    let mut stream = response.bytes_stream();
-                    while let Some(chunk) = stream.next().await {
-                        let chunk = chunk
-                            .with_context(|| "Failed to fetch chunk")?;
-
-                        downloaded += chunk.len() as u64;
-                        if let Some(pb) = &progress {
-                            pb.set_length(content_length);
-                            pb.set_position(min(downloaded, content_length));
-                        }
-
-                        if let Err(err) = tx.send(chunk.to_vec()) {
-                            match err.to_string().as_str() {
-                                "sending on a closed channel" => break,
-                                _ => anyhow::bail!("Failed to send chunk: {}", err),
-                            }
-                        }
-                    }
-                    drop(tx);
-                } else {
-                    anyhow::bail!("Failed to fetch {url}: {}", response.status());
+    // Invalid archives are removed, as resuming a corrupted download or keeping it in the cache is pointless
+    // Only archives verified against a checksum are cached, under their SHA-256 hash
+    match (verified, &hash) {
+        (true, Ok(hash)) if (checksum.is_some() || sha512.is_some()) && cached.is_none() && crate::archive::cache::is_enabled() => {
+            // The cache is optional, so failing to store the archive (e.g. a full or read-only disk) must not fail the install
+            if let Err(err) = crate::archive::cache::put(hash, filename, &archive) {
+                crate::warn!("Failed to cache {filename}: {err:#}");
+                if archive.exists() {
+                    std::fs::remove_file(&archive)
+                        .with_context(|| format!("Failed to remove {}", archive.display()))?;
                 }
+            }
+        }
+        (true, _) if cached.is_some() => {}
+        _ => {
+            std::fs::remove_file(&archive)
+                .with_context(|| format!("Failed to remove {}", archive.display()))?;
+        }
+    }
+
+    let hash = hash?;
+    if let Some(checksum) = checksum {
+        if checksum != hash {
+            anyhow::bail!("Checksum mismatch: expected {checksum}, got {hash}");
+        }
+    }
+
+    Ok(Archive { sha256: hash, size })
+}
+
+/// Download a file from a URL to the staging area, resuming any previous partial download of it.
+///
+/// The file is written to the staging area, which is the `downloads` folder of the cache directory.
+/// If the download is interrupted, it is resumed with a `Range` request, either when retrying or on the next call.
+/// When the expected size is known, the file is checked against it once downloaded.
+///
+/// Returns the path of the downloaded file.
+///
+/// # Errors
+/// This function will return an error if the download fails, or if the downloaded file does not have the expected size.
+pub fn download(url: &str, size: Option<u64>, progress: Option<&indicatif::ProgressBar>) -> anyhow::Result<PathBuf> {
+    let folder = staging_directory();
+    std::fs::create_dir_all(&folder)
+        .with_context(|| format!("Failed to create {}", folder.display()))?;
+
+    // The URL is part of the name, as different releases may share the same filename
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let path = folder.join(format!("{}-{}.part", &hash[..16], filename(url)?));
 
-                let hash = tokio::task::spawn_blocking(|| decoder_thread.join())
-                    .await
-                    .with_context(|| "Failed to join decoder thread")?
-                    .map_err(|e| anyhow::anyhow!("Decoder thread panicked: {:?}", e))??;
-
-                // If we have a checksum URL, we should check the hash
-                if let Some(checksum_url) = checksum_url {
-                    let checksum = client.get(checksum_url)
-                        .send()
-                        .await
-                        .with_context(|| format!("Failed to fetch {checksum_url}"))?
-                        .text()
-                        .await
-                        .with_context(|| format!("Failed to read {checksum_url}"))?;
-
-                    let checksum = checksum.split_whitespace().next().unwrap();
-                    if checksum.trim() != hash {
-                        anyhow::bail!("Checksum mismatch: expected {checksum}, got {hash}");
-                    }
+    block_on(async {
+        let client = crate::http::client()?;
+        crate::http::retry_async(|| resume(&client, url, &path, size, progress)).await
+    })?;
+
+    Ok(path)
+}
+
+/// Returns the staging area where downloads are written until they are extracted (see [`download`]).
+#[must_use]
+pub fn staging_directory() -> PathBuf {
+    Variable::CacheDirectory.get::<PathBuf>().join("downloads")
+}
+
+/// Extract an archive to a folder, stripping its top-level directory.
+///
+/// The format is detected from the archive's magic bytes or extension (see [`Format::detect`]).
+/// Tarballs are hashed while being extracted, while zip archives are hashed beforehand, as they cannot be streamed.
+/// Unsafe entries are skipped, and reported as warnings (see [`crate::archive::extract`]).
+///
+/// Returns the SHA-256 hash of the whole archive.
+///
+/// # Errors
+/// This function will return an error if the archive format is not supported, or if it cannot be read or extracted.
+pub fn extract(archive: &Path, folder: &Path) -> anyhow::Result<String> {
+    // Staged downloads have a .part suffix, which must be ignored to detect the format from the extension
+    let name = archive.file_name()
+        .map(|name| name.to_string_lossy().trim_end_matches(".part").to_string());
+    let format = Format::detect(archive, name.as_deref())?;
+    crate::debug!("Extracting {} as {format}", archive.display());
+
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut reader = HashRead::new(file);
+
+    let report = if format.is_tar() {
+        Some(
+            crate::archive::extract::tar(format.decoder(BufReader::new(&mut reader))?, folder)
+                .with_context(|| format!("Failed to extract to {}", folder.display()))?
+        )
+    } else {
+        None
+    };
+
+    // Make sure that trailing bytes are part of the hash as well (or the whole archive, for zip archives)
+    io::copy(&mut reader, &mut io::sink())
+        .with_context(|| format!("Failed to read {}", archive.display()))?;
+
+    let report = if let Some(report) = report {
+        report
+    } else {
+        let file = File::open(archive)
+            .with_context(|| format!("Failed to open {}", archive.display()))?;
+        crate::archive::extract::zip(BufReader::new(file), folder)
+            .with_context(|| format!("Failed to extract to {}", folder.display()))?
+    };
+
+    crate::debug!("Extracted {} entries from {}", report.extracted, archive.display());
+    if !report.rejected.is_empty() {
+        crate::warn!("Rejected {} unsafe entries from {}", report.rejected.len(), archive.display());
+    }
+
+    Ok(reader.hash())
+}
+
+/// Resume the download of a file, appending to whatever was already downloaded.
+async fn resume(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+    size: Option<u64>,
+    progress: Option<&indicatif::ProgressBar>,
+) -> anyhow::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut downloaded = file.metadata()
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+
+    if let Some(size) = size {
+        if downloaded == size {
+            crate::debug!("{url} is already downloaded");
+            if let Some(pb) = progress {
+                pb.set_length(size);
+                pb.set_position(size);
+            }
+            return Ok(());
+        }
+        if downloaded > size {
+            crate::debug!("Partial download of {url} is larger than expected, starting over");
+            file.set_len(0).await?;
+            downloaded = 0;
+        }
+    }
+
+    let response = loop {
+        let mut request = client.get(url);
+        if downloaded > 0 {
+            crate::debug!("Resuming download of {url} from {downloaded} bytes");
+            request = request.header(reqwest::header::RANGE, format!("bytes={downloaded}-"));
+        }
+
+        let response = request.send()
+            .await
+            .with_context(|| format!("Failed to fetch {url}"))?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT if range_start(&response) == Some(downloaded) => break response,
+            StatusCode::OK => {
+                if downloaded > 0 {
+                    crate::debug!("Server does not support resuming {url}, starting over");
+                    file.set_len(0).await?;
+                    downloaded = 0;
                 }
+                break response;
+            }
+            StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE if downloaded > 0 => {
+                crate::debug!("Server cannot resume {url} from {downloaded} bytes, starting over");
+                file.set_len(0).await?;
+                downloaded = 0;
+            }
+            status => {
+                response.error_for_status()
+                    .with_context(|| format!("Failed to fetch {url}"))?;
+                anyhow::bail!("Failed to fetch {url}: {status}");
+            }
+        }
+    };
+
+    let total = size.unwrap_or(downloaded + response.content_length().unwrap_or(0));
+    if let Some(pb) = progress {
+        pb.set_length(total);
+        pb.set_position(min(downloaded, total));
+        pb.reset_eta();
+    }
 
-                Ok(())
-            })
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = tokio::time::timeout(crate::http::read_timeout(), stream.next())
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))
+        .with_context(|| format!("Timed out while fetching {url}"))? {
+        let chunk = chunk
+            .with_context(|| "Failed to fetch chunk")?;
+
+        file.write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write to {}", path.display()))?;
+
+        downloaded += chunk.len() as u64;
+        if let Some(pb) = progress {
+            pb.set_position(min(downloaded, total));
+        }
+    }
+
+    file.flush()
+        .await
+        .with_context(|| format!("Failed to write to {}", path.display()))?;
+
+    match size {
+        Some(size) if downloaded < size => Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+            .with_context(|| format!("Download of {url} ended early ({downloaded} out of {size} bytes)")),
+        Some(size) if downloaded > size => {
+            drop(file);
+            tokio::fs::remove_file(path)
+                .await
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            anyhow::bail!("Download of {url} is larger than expected ({downloaded} out of {size} bytes)");
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the first byte of a partial response, from its `Content-Range` header (e.g. `bytes 100-199/200`).
+fn range_start(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Fetch the SHA-256 checksum of a download.
+///
+/// Checksums never change for a given URL, so they are kept in the cache directory once fetched.
+/// This allows reinstalling a cached archive while offline.
+fn fetch_checksum(url: &str) -> anyhow::Result<String> {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let path = Variable::CacheDirectory.get::<PathBuf>()
+        .join("checksums")
+        .join(format!("{}-{}", &hash[..16], filename(url)?));
+
+    if let Ok(checksum) = std::fs::read_to_string(&path) {
+        crate::debug!("Using cached checksum from {}", path.display());
+        return Ok(checksum.trim().to_string());
+    }
+
+    if Variable::Offline.get_bool() {
+        anyhow::bail!("No cached checksum found for {url}, cannot fetch it while offline");
+    }
+
+    let checksum = block_on(async {
+        let client = crate::http::client()?;
+
+        crate::http::retry_async(|| async {
+            client.get(url)
+                .timeout(crate::http::read_timeout())
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .with_context(|| format!("Failed to fetch {url}"))?
+                .text()
+                .await
+                .with_context(|| format!("Failed to read {url}"))
+        }).await
+    })?;
+
+    let checksum = checksum.split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("No checksum found in {url}"))?;
+
+    let stored = std::fs::create_dir_all(path.parent().unwrap())
+        .and_then(|()| std::fs::write(&path, &checksum));
+    if let Err(err) = stored {
+        crate::debug!("Failed to cache checksum to {}: {err}", path.display());
+    }
+
+    Ok(checksum)
+}
+
+/// Returns the filename of a URL, without its query string.
+pub(crate) fn filename(url: &str) -> anyhow::Result<&str> {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|url| url.split('/').next_back())
+        .filter(|filename| !filename.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Failed to get filename from {url}"))
+}
+
+/// Run a future to completion on a new runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| {
+        tokio::runtime::Runtime::new()
+            .expect("Failed to create async runtime")
+            .block_on(future)
     })
 }
 
-/// A read-only channel, used for decoding a tarball.
-struct ChannelRead {
-    rx: flume::Receiver<Vec<u8>>,
-    current: io::Cursor<Vec<u8>>,
+/// A reader that hashes everything read through it.
+struct HashRead<R> {
+    inner: R,
     hash: Sha256,
 }
 
-impl ChannelRead {
-    fn new(rx: flume::Receiver<Vec<u8>>) -> Self {
+impl<R: Read> HashRead<R> {
+    fn new(inner: R) -> Self {
         Self {
-            rx,
-            current: io::Cursor::new(vec![]),
+            inner,
             hash: Sha256::new(),
         }
     }
@@ -139,58 +448,196 @@ impl ChannelRead {
     }
 }
 
-impl Read for ChannelRead {
+impl<R: Read> Read for HashRead<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.current.position() == self.current.get_ref().len() as u64 {
-            if let Ok(data) = self.rx.recv() {
-                self.hash.update(&data);
-                self.current = io::Cursor::new(data);
+        let read = self.inner.read(buf)?;
+        self.hash.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Check that there is enough free space on the filesystems of the given paths.
+///
+/// Each requirement is a path and the number of bytes that will be written to it.
+/// Paths do not have to exist yet, and requirements on the same filesystem are added up.
+/// Filesystems whose free space cannot be determined are skipped (logged as debug).
+///
+/// # Errors
+/// This function will return an error if any filesystem does not have enough free space.
+pub fn check_space(requirements: &[(PathBuf, u64)]) -> anyhow::Result<()> {
+    let mut filesystems: Vec<(u64, PathBuf, u64, u64)> = vec![];
+
+    for (path, required) in requirements {
+        let (device, available) = match free_space(path) {
+            Ok(space) => space,
+            Err(err) => {
+                crate::debug!("Skipping free space check for {}: {err:#}", path.display());
+                continue;
             }
+        };
+
+        if let Some(filesystem) = filesystems.iter_mut().find(|(dev, ..)| *dev == device) {
+            filesystem.2 += required;
+        } else {
+            filesystems.push((device, path.clone(), *required, available));
         }
+    }
 
-        self.current.read(buf)
+    for (_, path, required, available) in filesystems {
+        crate::debug!("{} needs {required} bytes, {available} available", path.display());
+
+        if required > available {
+            anyhow::bail!(
+                "Not enough free space for {}: {} needed, only {} available",
+                path.display(),
+                humansize::format_size(required, humansize::DECIMAL),
+                humansize::format_size(available, humansize::DECIMAL),
+            );
+        }
     }
+
+    Ok(())
 }
 
-/// Strip the content of a folder, moving all files and folders one level up.
+/// Returns the device and the free space (in bytes) of the filesystem of a path.
 ///
-/// # Errors
-/// This function will return an error if any file or folder cannot be moved.
+/// If the path does not exist yet, its closest existing ancestor is used.
+fn free_space(path: &Path) -> anyhow::Result<(u64, u64)> {
+    let existing = path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .ok_or_else(|| anyhow::anyhow!("No existing ancestor for {}", path.display()))?;
+
+    let device = std::fs::metadata(existing)
+        .with_context(|| format!("Failed to read {}", existing.display()))?
+        .dev();
+    let stat = nix::sys::statvfs::statvfs(existing)
+        .with_context(|| format!("Failed to get free space of {}", existing.display()))?;
+
+    // The types of these fields depend on the architecture
+    #[allow(clippy::useless_conversion)]
+    let available = u64::from(stat.blocks_available()).saturating_mul(u64::from(stat.fragment_size()));
+
+    Ok((device, available))
+}
+
+/// Convert an HTML snippet to markdown, for displaying it in the terminal.
 ///
-/// # Panics
-/// This function will panic if the folder cannot be read or written to.
-pub fn strip_content(folder: &PathBuf) -> anyhow::Result<()> {
-    let entries = std::fs::read_dir(folder)
-        .with_context(|| format!("Failed to read {}", folder.display()))?;
-    for entry in entries {
-        let entry = entry
-            .with_context(|| format!("Failed to read {}", folder.display()))?;
-
-        if !entry.file_type().unwrap().is_dir() {
-            let path = entry.path();
-            let new_path = folder.join(path.file_name().unwrap());
-            std::fs::rename(&path, &new_path)
-                .with_context(|| format!("Failed to move {} to {}", path.display(), new_path.display()))?;
+/// This only supports the small subset of HTML used in release notes (headers, paragraphs, lists, emphasis and code).
+/// Any other tag is stripped, keeping its content.
+#[must_use]
+pub fn html_to_markdown(html: &str) -> String {
+    let mut markdown = String::new();
+    let mut lists = 0usize;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut markdown, &rest[..start]);
+
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let tag = rest[start + 1..start + end].trim().to_lowercase();
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        match (name, closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                markdown.push_str(&format!("\n\n{} ", "#".repeat(level)));
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div", _) => markdown.push_str("\n\n"),
+            ("br", _) => markdown.push('\n'),
+            ("ul" | "ol", false) => {
+                lists += 1;
+                markdown.push('\n');
+            }
+            ("ul" | "ol", true) => {
+                lists = lists.saturating_sub(1);
+                markdown.push('\n');
+            }
+            ("li", false) => markdown.push_str(&format!("\n{}* ", "  ".repeat(lists.saturating_sub(1)))),
+            ("b" | "strong", _) => markdown.push_str("**"),
+            ("i" | "em", _) => markdown.push('*'),
+            ("code", _) => markdown.push('`'),
+            _ => {}
+        }
+    }
+    push_text(&mut markdown, rest);
+
+    // Clean up the blank lines and trailing spaces left by the tags
+    let mut lines: Vec<&str> = vec![];
+    for line in markdown.lines().map(str::trim_end) {
+        if line.trim().is_empty() && lines.last().map_or(true, |last| last.is_empty()) {
             continue;
         }
+        lines.push(if line.trim().is_empty() { "" } else { line });
+    }
 
-        let path = entry.path();
-        let entries = std::fs::read_dir(&path)
-            .with_context(|| format!("Failed to read {}", path.display()))?;
+    lines.join("\n").trim().to_string()
+}
 
-        for entry in entries {
-            let entry = entry
-                .with_context(|| format!("Failed to read {}", path.display()))?;
-            let path = entry.path();
-            let new_path = folder.join(path.file_name().unwrap());
-            std::fs::rename(&path, &new_path)
-                .with_context(|| format!("Failed to move {} to {}", path.display(), new_path.display()))?;
-        }
+/// Push text content from HTML to markdown, collapsing whitespace and decoding entities.
+fn push_text(markdown: &mut String, text: &str) {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let separated = markdown.is_empty() || markdown.ends_with(char::is_whitespace);
 
-        std::fs::remove_dir(&path)
-            .with_context(|| format!("Failed to remove {}", path.display()))?;
+    // Whitespace around text is kept as a single space, except at the start of a line
+    if text.starts_with(char::is_whitespace) && !separated {
+        markdown.push(' ');
     }
-    Ok(())
+    if words.is_empty() {
+        return;
+    }
+
+    markdown.push_str(&decode_entities(&words.join(" ")));
+
+    if text.ends_with(char::is_whitespace) {
+        markdown.push(' ');
+    }
+}
+
+/// Decode the most common HTML entities.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .map_or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()), |n| u32::from_str_radix(n, 16).ok())
+                .and_then(char::from_u32),
+        });
+
+        if let (Some(entity), Some(character)) = (entity, character) {
+            decoded.push(character);
+            rest = &rest[entity.len() + 2..];
+        } else {
+            decoded.push('&');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
 }
 
 /// Show a desktop notification.
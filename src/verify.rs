@@ -0,0 +1,246 @@
+//! Signature verification
+//!
+//! This module checks detached signatures of downloaded archives (e.g. `ideaIU-2024.1.tar.gz.minisig`) against the
+//! public keys found in the keys directory, on top of the checksum verification.
+//! Each signature scheme is a [`Verifier`], and the [`Policy`] decides whether a missing or invalid signature is
+//! ignored, reported, or fatal.
+
+pub mod minisign;
+pub mod openpgp;
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use anyhow::Context;
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use crate::env::Variable;
+
+pub use minisign::Minisign;
+pub use openpgp::OpenPgp;
+
+/// What to do with the signature of a downloaded archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Policy {
+    /// Signatures are not checked
+    #[default]
+    Off,
+    /// Signatures are checked, but a missing or invalid signature only prints a warning
+    Warn,
+    /// Signatures are checked, and a missing or invalid signature fails the download
+    Require,
+}
+
+impl Policy {
+    /// Returns the policy set by the environment.
+    ///
+    /// Invalid values fall back to the default policy, with a warning.
+    #[must_use]
+    pub fn get() -> Self {
+        let value = Variable::SignaturePolicy.get::<String>();
+
+        value.parse().unwrap_or_else(|err| {
+            crate::warn!("{err}, using the default value");
+            Self::default()
+        })
+    }
+
+    /// Returns the string representation of the policy.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Warn => "warn",
+            Self::Require => "require",
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "false" | "0" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "require" | "true" | "1" => Ok(Self::Require),
+            _ => anyhow::bail!("Invalid signature policy {s} for {} (expected off, warn or require)", Variable::SignaturePolicy.env()),
+        }
+    }
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A signature scheme, able to verify detached signatures against a set of trusted keys.
+pub trait Verifier: Send + Sync {
+    /// Returns the name of the signature scheme (e.g. minisign).
+    fn name(&self) -> &'static str;
+
+    /// Returns the extensions appended to the URL of an archive to get its detached signature, in order of preference.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Returns the number of trusted keys.
+    fn keys(&self) -> usize;
+
+    /// Verifies a file against a detached signature.
+    ///
+    /// Returns a description of the key that made the signature.
+    ///
+    /// # Errors
+    /// This function will return an error if the signature is malformed, was not made by a trusted key, or does not match the file.
+    fn verify(&self, file: &Path, signature: &[u8]) -> anyhow::Result<String>;
+}
+
+/// Returns the directory of trusted public keys.
+#[must_use]
+pub fn directory() -> PathBuf {
+    Variable::KeysDirectory.get::<PathBuf>()
+}
+
+/// Returns a verifier for each signature scheme that has at least one trusted key in the keys directory.
+///
+/// # Errors
+/// This function will return an error if the keys directory cannot be read, or if a key is invalid.
+pub fn verifiers() -> anyhow::Result<Vec<Box<dyn Verifier>>> {
+    let directory = directory();
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&directory)
+        .with_context(|| format!("Failed to read {}", directory.display()))?;
+
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to read {}", directory.display()))?;
+    paths.sort();
+
+    let mut minisign = Minisign::default();
+    let mut openpgp = OpenPgp::default();
+
+    for path in paths.into_iter().filter(|path| path.is_file()) {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pub") => minisign.add_key(&path)?,
+            Some("asc" | "gpg") => openpgp.add_key(&path),
+            _ => crate::debug!("Ignoring {}, which is not a known key format", path.display()),
+        }
+    }
+
+    let mut verifiers: Vec<Box<dyn Verifier>> = Vec::new();
+    if minisign.keys() > 0 {
+        verifiers.push(Box::new(minisign));
+    }
+    if openpgp.keys() > 0 {
+        verifiers.push(Box::new(openpgp));
+    }
+
+    Ok(verifiers)
+}
+
+/// Verifies the detached signature of an archive downloaded from a URL, according to the signature policy.
+///
+/// # Errors
+/// This function will return an error if the policy requires a signature, and none could be verified.
+pub fn verify(file: &Path, url: &str) -> anyhow::Result<()> {
+    let policy = Policy::get();
+    if policy == Policy::Off {
+        return Ok(());
+    }
+
+    match check(file, url) {
+        Ok(signer) => {
+            crate::debug!("Verified signature of {url}, signed by {signer}");
+            Ok(())
+        }
+        Err(err) if policy == Policy::Warn => {
+            crate::warn!("Could not verify the signature of {url}: {err:#}");
+            Ok(())
+        }
+        Err(err) => Err(err.context(format!("Could not verify the signature of {url}"))),
+    }
+}
+
+/// Verifies the detached signature of an archive with the first scheme for which a signature is published.
+fn check(file: &Path, url: &str) -> anyhow::Result<String> {
+    let verifiers = verifiers()?;
+    if verifiers.is_empty() {
+        anyhow::bail!("No trusted keys found in {}", directory().display());
+    }
+
+    for verifier in &verifiers {
+        for extension in verifier.extensions() {
+            let Some(signature) = fetch_signature(&signature_url(url, extension))? else {
+                continue;
+            };
+
+            return verifier.verify(file, &signature)
+                .with_context(|| format!("Invalid {} signature", verifier.name()));
+        }
+    }
+
+    let extensions = verifiers.iter()
+        .flat_map(|verifier| verifier.extensions())
+        .copied()
+        .collect::<Vec<_>>();
+    anyhow::bail!("No signature found (tried {})", extensions.join(", "))
+}
+
+/// Returns the URL of a detached signature, keeping the query string of the archive URL (if any).
+fn signature_url(url: &str, extension: &str) -> String {
+    match url.split_once('?') {
+        Some((path, query)) => format!("{path}{extension}?{query}"),
+        None => format!("{url}{extension}"),
+    }
+}
+
+/// Fetch a detached signature, returning `None` if it is not published.
+///
+/// Signatures never change for a given URL, so they are kept in the cache directory once fetched.
+/// This allows reinstalling a cached archive while offline.
+fn fetch_signature(url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let path = Variable::CacheDirectory.get::<PathBuf>()
+        .join("signatures")
+        .join(format!("{}-{}", &hash[..16], crate::util::filename(url)?));
+
+    if let Ok(signature) = std::fs::read(&path) {
+        crate::debug!("Using cached signature from {}", path.display());
+        return Ok(Some(signature));
+    }
+
+    if Variable::Offline.get_bool() {
+        crate::debug!("No cached signature found for {url}, cannot fetch it while offline");
+        return Ok(None);
+    }
+
+    let client = crate::http::blocking()?;
+    let signature = crate::http::retry(|| {
+        let response = client.get(url)
+            .send()
+            .with_context(|| format!("Failed to fetch {url}"))?;
+
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::FORBIDDEN) {
+            return Ok(None);
+        }
+
+        response.error_for_status()
+            .and_then(|response| response.bytes())
+            .map(|bytes| Some(bytes.to_vec()))
+            .with_context(|| format!("Failed to fetch {url}"))
+    })?;
+
+    if let Some(signature) = &signature {
+        let stored = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|()| std::fs::write(&path, signature));
+        if let Err(err) = stored {
+            crate::debug!("Failed to cache signature to {}: {err}", path.display());
+        }
+    }
+
+    Ok(signature)
+}
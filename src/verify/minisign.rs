@@ -0,0 +1,166 @@
+//! Minisign signatures
+//!
+//! This module verifies signatures made with [minisign](https://jedisct1.github.io/minisign/), both prehashed (the
+//! default since minisign 0.8) and legacy ones. Public keys are the `.pub` files generated by `minisign -G`.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blake2::{Blake2b512, Digest};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use super::Verifier;
+
+/// The algorithm of legacy signatures, made over the whole file.
+const ALGORITHM_LEGACY: &[u8; 2] = b"Ed";
+/// The algorithm of prehashed signatures, made over the BLAKE2b-512 hash of the file.
+const ALGORITHM_HASHED: &[u8; 2] = b"ED";
+/// The prefix of the trusted comment, which is signed along with the signature.
+const TRUSTED_COMMENT: &str = "trusted comment: ";
+
+/// Verifies minisign signatures against a set of public keys.
+#[derive(Debug, Clone, Default)]
+pub struct Minisign {
+    keys: Vec<PublicKey>,
+}
+
+/// A minisign public key.
+#[derive(Debug, Clone)]
+struct PublicKey {
+    id: [u8; 8],
+    key: [u8; 32],
+    path: PathBuf,
+}
+
+impl Minisign {
+    /// Adds a trusted public key from a `.pub` file.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be read, or is not a valid minisign public key.
+    pub fn add_key(&mut self, path: &Path) -> anyhow::Result<()> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let key = PublicKey::parse(&content, path)
+            .with_context(|| format!("Invalid minisign public key {}", path.display()))?;
+
+        crate::debug!("Trusting minisign key {} from {}", key_id(&key.id), path.display());
+        self.keys.push(key);
+        Ok(())
+    }
+}
+
+impl Verifier for Minisign {
+    fn name(&self) -> &'static str {
+        "minisign"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".minisig"]
+    }
+
+    fn keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn verify(&self, file: &Path, signature: &[u8]) -> anyhow::Result<String> {
+        let signature = std::str::from_utf8(signature)
+            .with_context(|| "Signature is not valid UTF-8")?;
+
+        // The untrusted comment comes first, then the signature, the trusted comment and its own signature
+        let mut lines = signature.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("untrusted comment:"));
+
+        let (Some(encoded), Some(comment), Some(global)) = (lines.next(), lines.next(), lines.next()) else {
+            anyhow::bail!("Signature is incomplete");
+        };
+
+        let decoded = STANDARD.decode(encoded)
+            .with_context(|| "Signature is not valid base64")?;
+        if decoded.len() != 74 {
+            anyhow::bail!("Signature has an unexpected length of {} bytes", decoded.len());
+        }
+        let (algorithm, rest) = decoded.split_at(2);
+        let (id, signature) = rest.split_at(8);
+
+        let Some(key) = self.keys.iter().find(|key| key.id == id) else {
+            anyhow::bail!("Signed by the untrusted key {}", key_id(id));
+        };
+        let public_key = UnparsedPublicKey::new(&ED25519, key.key);
+
+        let message = if algorithm == ALGORITHM_HASHED {
+            hash(file)?
+        } else if algorithm == ALGORITHM_LEGACY {
+            std::fs::read(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?
+        } else {
+            anyhow::bail!("Unsupported signature algorithm {}", String::from_utf8_lossy(algorithm));
+        };
+
+        public_key.verify(&message, signature)
+            .map_err(|_| anyhow::anyhow!("Signature does not match {}", file.display()))?;
+
+        // The trusted comment is signed along with the signature, so that it cannot be tampered with either
+        let Some(comment) = comment.strip_prefix(TRUSTED_COMMENT) else {
+            anyhow::bail!("Signature has no trusted comment");
+        };
+        let global = STANDARD.decode(global)
+            .with_context(|| "Trusted comment signature is not valid base64")?;
+
+        public_key.verify(&[signature, comment.as_bytes()].concat(), &global)
+            .map_err(|_| anyhow::anyhow!("Trusted comment signature does not match"))?;
+
+        Ok(format!("minisign key {} from {} ({comment})", key_id(&key.id), key.path.display()))
+    }
+}
+
+impl PublicKey {
+    fn parse(content: &str, path: &Path) -> anyhow::Result<Self> {
+        let Some(encoded) = content.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+        else {
+            anyhow::bail!("No key found");
+        };
+
+        let decoded = STANDARD.decode(encoded)
+            .with_context(|| "Key is not valid base64")?;
+        if decoded.len() != 42 {
+            anyhow::bail!("Key has an unexpected length of {} bytes", decoded.len());
+        }
+        if &decoded[..2] != ALGORITHM_LEGACY {
+            anyhow::bail!("Unsupported key algorithm {}", String::from_utf8_lossy(&decoded[..2]));
+        }
+
+        let mut id = [0; 8];
+        id.copy_from_slice(&decoded[2..10]);
+        let mut key = [0; 32];
+        key.copy_from_slice(&decoded[10..]);
+
+        Ok(Self {
+            id,
+            key,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// Returns the BLAKE2b-512 hash of a file, as signed by prehashed signatures.
+fn hash(file: &Path) -> anyhow::Result<Vec<u8>> {
+    let mut reader = File::open(file)
+        .with_context(|| format!("Failed to open {}", file.display()))?;
+
+    let mut hasher = Blake2b512::new();
+    io::copy(&mut reader, &mut hasher)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Returns a key ID as displayed by minisign (e.g. `E7620F1842B4E81F`).
+fn key_id(id: &[u8]) -> String {
+    id.iter().rev().map(|byte| format!("{byte:02X}")).collect()
+}
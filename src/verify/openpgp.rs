@@ -0,0 +1,109 @@
+//! `OpenPGP` signatures
+//!
+//! This module verifies `OpenPGP` signatures with `GnuPG`, which must be installed.
+//! Keys are imported into a temporary home directory for each verification, so that only the keys from the keys
+//! directory are trusted, and the user's own keyring is left untouched.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use anyhow::Context;
+use super::Verifier;
+
+/// The `GnuPG` executable.
+const GPG: &str = "gpg";
+
+/// Verifies `OpenPGP` signatures against a set of public keys, using `GnuPG`.
+#[derive(Debug, Clone, Default)]
+pub struct OpenPgp {
+    keys: Vec<PathBuf>,
+}
+
+impl OpenPgp {
+    /// Adds a trusted public key (or keyring), either ASCII-armored (`.asc`) or binary (`.gpg`).
+    ///
+    /// Keys are only parsed when verifying a signature.
+    pub fn add_key(&mut self, path: &Path) {
+        crate::debug!("Trusting OpenPGP keys from {}", path.display());
+        self.keys.push(path.to_path_buf());
+    }
+}
+
+impl Verifier for OpenPgp {
+    fn name(&self) -> &'static str {
+        "OpenPGP"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".asc", ".sig"]
+    }
+
+    fn keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn verify(&self, file: &Path, signature: &[u8]) -> anyhow::Result<String> {
+        let home = tempfile::tempdir()
+            .with_context(|| "Failed to create a temporary GnuPG home")?;
+
+        let import = gpg(home.path())
+            .arg("--import")
+            .args(&self.keys)
+            .output();
+        check(import, "import the trusted keys")?;
+
+        let path = home.path().join("signature");
+        std::fs::write(&path, signature)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        let output = gpg(home.path())
+            .args(["--status-fd", "1", "--verify"])
+            .arg(&path)
+            .arg(file)
+            .output();
+        let output = check(output, "verify the signature")?;
+
+        // Only the status lines are machine-readable, the rest of the output depends on the locale
+        let status = String::from_utf8_lossy(&output.stdout);
+        let mut fingerprint = None;
+        let mut user = None;
+        for line in status.lines() {
+            let mut fields = line.strip_prefix("[GNUPG:] ").unwrap_or_default().splitn(3, ' ');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some("VALIDSIG"), Some(value), _) => fingerprint = Some(value.to_string()),
+                (Some("GOODSIG"), Some(_), Some(value)) => user = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let Some(fingerprint) = fingerprint else {
+            anyhow::bail!("Signature does not match {}", file.display());
+        };
+
+        Ok(match user {
+            Some(user) => format!("OpenPGP key {fingerprint} ({user})"),
+            None => format!("OpenPGP key {fingerprint}"),
+        })
+    }
+}
+
+/// Returns a `GnuPG` command using the given home directory.
+fn gpg(home: &Path) -> Command {
+    let mut command = Command::new(GPG);
+    command.arg("--homedir")
+        .arg(home)
+        .args(["--batch", "--no-tty", "--quiet"]);
+    command
+}
+
+/// Checks that a `GnuPG` command succeeded, including its error output otherwise.
+fn check(output: std::io::Result<Output>, action: &str) -> anyhow::Result<Output> {
+    let output = output
+        .with_context(|| format!("Failed to run {GPG}, is GnuPG installed?"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to {action}: {}", stderr.trim());
+    }
+
+    Ok(output)
+}